@@ -0,0 +1,222 @@
+//! Measures every algorithm over a few sizes and distributions,
+//! comparing running them step by step against the standard library sorts.
+//!
+//! Results are printed as CSV (the default) or JSON, one row per algorithm, distribution and size,
+//! so they can be saved and compared across commits:
+//!
+//! ```text
+//! cargo bench --bench sorters -- --format json --sizes 100,1000 > bench.json
+//! ```
+
+use rand::{rngs::StdRng, SeedableRng};
+use sorting_visualizer::algorithms::bogo_sort::MAX_PRACTICAL_SIZE;
+use sorting_visualizer::{Algorithms, Distribution, Stats};
+use std::{
+    env,
+    hint::black_box,
+    process::ExitCode,
+    time::{Duration, Instant},
+};
+use strum::IntoEnumIterator;
+
+const USAGE: &str = "\
+Usage: cargo bench --bench sorters -- [OPTIONS]
+
+Options:
+  --sizes <N,...>    Sizes to measure [default: 64,512,2048]
+  --format <FORMAT>  Either csv or json [default: csv]";
+
+/// Sizes measured when none are given.
+const SIZES: [usize; 3] = [64, 512, 2048];
+/// How many times each measurement is repeated, the median is reported.
+const REPETITIONS: usize = 5;
+/// Numbers are generated from `FLOOR` (inclusive) to `CEIL` (exclusive).
+const FLOOR: usize = 1;
+const CEIL: usize = 10_000;
+/// The numbers are always generated from this seed, so every commit measures the same input.
+const SEED: u64 = 0;
+
+const COLUMNS: [&str; 14] = [
+    "algorithm",
+    "distribution",
+    "size",
+    "steps",
+    "comparisons",
+    "swaps",
+    "writes",
+    "aux_writes",
+    "run_ns",
+    "step_ns",
+    "sort_ns",
+    "sort_unstable_ns",
+    "sort_overhead",
+    "sort_unstable_overhead",
+];
+
+#[derive(PartialEq, Clone, Copy)]
+enum Format {
+    Csv,
+    Json,
+}
+
+/// The measurements of an algorithm over one input.
+struct Measurement {
+    algorithm: Algorithms,
+    distribution: Distribution,
+    size: usize,
+    stats: Stats,
+    /// Median time of `Sorter::run`, which also counts the operations of every step.
+    run: Duration,
+    /// Median time of calling `Sorter::step` until sorted, without counting anything.
+    stepping: Duration,
+    /// Median time of `slice::sort` over the same input.
+    sort: Duration,
+    /// Median time of `slice::sort_unstable` over the same input.
+    sort_unstable: Duration,
+}
+
+impl Measurement {
+    fn values(&self) -> Vec<String> {
+        let stepping = self.stepping.as_nanos();
+        let steps = self.stats.steps.max(1) as u128;
+        let overhead = |native: Duration| stepping as f64 / native.as_nanos().max(1) as f64;
+        vec![
+            format!("{:?}", self.algorithm),
+            self.distribution.to_string(),
+            self.size.to_string(),
+            self.stats.steps.to_string(),
+            self.stats.comparisons.to_string(),
+            self.stats.swaps.to_string(),
+            self.stats.writes.to_string(),
+            self.stats.aux_writes.to_string(),
+            self.run.as_nanos().to_string(),
+            (stepping / steps).to_string(),
+            self.sort.as_nanos().to_string(),
+            self.sort_unstable.as_nanos().to_string(),
+            format!("{:.2}", overhead(self.sort)),
+            format!("{:.2}", overhead(self.sort_unstable)),
+        ]
+    }
+}
+
+/// Times `f` over a fresh copy of `input` a few times and returns the median.
+fn median(input: &[usize], mut f: impl FnMut(&mut Vec<usize>)) -> Duration {
+    let mut times: Vec<Duration> = (0..REPETITIONS)
+        .map(|_| {
+            let mut numbers = input.to_vec();
+            let start = Instant::now();
+            f(black_box(&mut numbers));
+            let elapsed = start.elapsed();
+            black_box(numbers);
+            elapsed
+        })
+        .collect();
+    times.sort();
+    times[REPETITIONS / 2]
+}
+
+fn measure(algorithm: Algorithms, distribution: Distribution, size: usize) -> Measurement {
+    let input = distribution.generate(FLOOR, CEIL, size, &mut StdRng::seed_from_u64(SEED));
+    let mut sorter = algorithm.sorter();
+    sorter.seed(SEED);
+    let stats = sorter.run(&mut input.clone());
+    Measurement {
+        algorithm,
+        distribution,
+        size,
+        stats,
+        run: median(&input, |numbers| {
+            sorter.run(numbers);
+        }),
+        stepping: median(&input, |numbers| {
+            while !sorter.step(numbers) {}
+            sorter.reset_state();
+        }),
+        sort: median(&input, |numbers| numbers.sort()),
+        sort_unstable: median(&input, |numbers| numbers.sort_unstable()),
+    }
+}
+
+fn print(measurements: &[Measurement], format: Format) {
+    match format {
+        Format::Csv => {
+            println!("{}", COLUMNS.join(","));
+            for measurement in measurements {
+                println!("{}", measurement.values().join(","));
+            }
+        }
+        Format::Json => {
+            let rows: Vec<String> = measurements
+                .iter()
+                .map(|measurement| {
+                    let fields: Vec<String> = COLUMNS
+                        .iter()
+                        .zip(measurement.values())
+                        .enumerate()
+                        .map(|(i, (column, value))| match i {
+                            // The first two columns are names, the rest are numbers
+                            0 | 1 => format!(r#""{column}":"{value}""#),
+                            _ => format!(r#""{column}":{value}"#),
+                        })
+                        .collect();
+                    format!("{{{}}}", fields.join(","))
+                })
+                .collect();
+            println!("[{}]", rows.join(",\n"));
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let mut format = Format::Csv;
+    let mut sizes = SIZES.to_vec();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => match args.next().as_deref() {
+                Some("csv") => format = Format::Csv,
+                Some("json") => format = Format::Json,
+                _ => {
+                    eprintln!("--format must be either csv or json");
+                    return ExitCode::from(2);
+                }
+            },
+            "--sizes" => {
+                match args
+                    .next()
+                    .unwrap_or_default()
+                    .split(',')
+                    .map(str::parse)
+                    .collect()
+                {
+                    Ok(parsed) => sizes = parsed,
+                    Err(_) => {
+                        eprintln!("--sizes must be a comma separated list of numbers");
+                        return ExitCode::from(2);
+                    }
+                }
+            }
+            // Cargo passes `--bench` to every benchmark
+            "--bench" => {}
+            _ => {
+                eprintln!("unknown argument {arg}\n\n{USAGE}");
+                return ExitCode::from(2);
+            }
+        }
+    }
+
+    let mut measurements = vec![];
+    for algorithm in Algorithms::iter() {
+        for distribution in Distribution::iter() {
+            for &size in &sizes {
+                if algorithm == Algorithms::Bogo && size > MAX_PRACTICAL_SIZE {
+                    continue;
+                }
+                eprintln!("Measuring {algorithm:?}Sort over {size} {distribution} numbers");
+                measurements.push(measure(algorithm, distribution, size));
+            }
+        }
+    }
+    print(&measurements, format);
+    ExitCode::SUCCESS
+}
@@ -1,43 +1,45 @@
-use super::{Reasons, Sorter};
+use super::{Operation, Sorter};
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::{thread_rng, Rng, SeedableRng};
 
+/// Largest size worth sorting when BogoSort runs many times, such as when it's measured,
+/// as the shuffles it takes grow with the factorial of the size.
+pub const MAX_PRACTICAL_SIZE: usize = 8;
+
+/// Shuffles the numbers until they happen to be sorted.
+#[derive(Clone)]
 pub struct BogoSort {
-    reason: Reasons,
+    operations: Vec<Operation>,
     needs_shuffle: bool,
-    shuffled: bool,
     curr: usize,
     x: usize,
     y: usize,
+    // Owning the generator makes clones shuffle the same way
+    rng: StdRng,
+    seed: u64,
 }
 
 impl Sorter for BogoSort {
     fn new() -> BogoSort {
+        let seed = thread_rng().gen();
         BogoSort {
-            reason: Reasons::Comparing,
+            operations: vec![],
             needs_shuffle: false,
-            shuffled: false,
             curr: 1,
             x: 0,
             y: 1,
+            rng: StdRng::seed_from_u64(seed),
+            seed,
         }
     }
 
-    fn reason(&self) -> super::Reasons {
-        self.reason
-    }
-
-    fn special(&self) -> (usize, usize) {
-        if self.shuffled {
-            return (usize::MAX, usize::MAX);
-        }
-        if self.curr != 1 {
-            return (self.x, self.y);
-        }
-        (usize::MAX, usize::MAX)
+    fn operations(&self) -> &[Operation] {
+        &self.operations
     }
 
     fn step(&mut self, array: &mut Vec<usize>) -> bool {
+        self.operations.clear();
         if self.needs_shuffle {
             self.switch(array);
         } else {
@@ -47,34 +49,40 @@ impl Sorter for BogoSort {
     }
 
     fn modify_state(&mut self, array: &[usize]) -> bool {
-        if self.curr == array.len() {
+        if self.curr >= array.len() {
             return true;
         }
-        if self.y < array.len() - 1 && !self.shuffled {
-            self.x = self.curr - 1;
-            self.y = self.curr;
-            self.curr += 1;
-        }
-        if self.shuffled {
-            self.x = 0;
-            self.y = 1;
-            self.curr = 2;
-        }
+        self.x = self.curr - 1;
+        self.y = self.curr;
+        self.curr += 1;
         self.needs_shuffle = array[self.y] < array[self.x];
-        self.reason = Reasons::Comparing;
-        self.shuffled = false;
+        self.operations.push(Operation::Compare(self.x, self.y));
         false
     }
 
     fn switch(&mut self, array: &mut Vec<usize>) {
-        array.shuffle(&mut thread_rng());
-        self.shuffled = true;
+        array.shuffle(&mut self.rng);
+        // Shuffling may move every number, so we consider all of them written
+        self.operations.extend(
+            array
+                .iter()
+                .enumerate()
+                .map(|(i, &n)| Operation::Write(i, n)),
+        );
         self.needs_shuffle = false;
-        self.curr = 2;
+        // After shuffling we check the order from the start again
+        self.curr = 1;
     }
 
     fn reset_state(&mut self) {
+        let seed = self.seed;
         *self = BogoSort::new();
+        self.seed(seed);
+    }
+
+    fn seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = StdRng::seed_from_u64(seed);
     }
 }
 
@@ -87,6 +95,7 @@ mod tests {
         },
         util,
     };
+    use rand::thread_rng;
 
     #[test]
     fn run() {
@@ -95,7 +104,7 @@ mod tests {
 
         for _ in 0..REPETITIONS {
             let mut sorter = BogoSort::new();
-            let mut array = util::gen_random_vector(FLOOR, CEIL, size);
+            let mut array = util::gen_random_vector(FLOOR, CEIL, size, &mut thread_rng());
 
             let mut expected = array.clone();
             expected.sort();
@@ -105,4 +114,23 @@ mod tests {
             assert_eq!(array, expected);
         }
     }
+
+    #[test]
+    fn seed() {
+        let array = util::gen_random_vector(FLOOR, CEIL, 5, &mut thread_rng());
+        let mut first = BogoSort::new();
+        let mut second = BogoSort::new();
+        first.seed(42);
+        second.seed(42);
+
+        let (mut a, mut b) = (array.clone(), array);
+        loop {
+            let done = first.step(&mut a);
+            assert_eq!(done, second.step(&mut b));
+            assert_eq!(first.operations(), second.operations());
+            if done {
+                break;
+            }
+        }
+    }
 }
@@ -1,10 +1,12 @@
-use super::{Reasons, Sorter};
+use super::{Operation, Sorter};
 
+/// Compares adjacent numbers, switching them if out of order, until no switches are needed.
+#[derive(Clone)]
 pub struct BubbleSort {
     x: usize,
     y: usize,
     needs_switch: bool,
-    reason: Reasons,
+    operations: Vec<Operation>,
 }
 
 impl Sorter for BubbleSort {
@@ -13,23 +15,16 @@ impl Sorter for BubbleSort {
             x: 0,
             y: usize::MAX,
             needs_switch: false,
-            reason: Reasons::Comparing,
+            operations: vec![],
         }
     }
 
-    fn special(&self) -> (usize, usize) {
-        if self.y != usize::MAX {
-            (self.y, self.y + 1)
-        } else {
-            (usize::MAX, usize::MAX)
-        }
-    }
-
-    fn reason(&self) -> super::Reasons {
-        self.reason
+    fn operations(&self) -> &[Operation] {
+        &self.operations
     }
 
     fn step(&mut self, array: &mut Vec<usize>) -> bool {
+        self.operations.clear();
         if self.needs_switch {
             self.switch(array);
         } else {
@@ -39,7 +34,7 @@ impl Sorter for BubbleSort {
     }
 
     fn modify_state(&mut self, array: &[usize]) -> bool {
-        if self.x == array.len() - 1 {
+        if self.x + 1 >= array.len() {
             return true;
         }
         if self.y < array.len() - 1 - self.x {
@@ -49,13 +44,13 @@ impl Sorter for BubbleSort {
             self.y = 0;
         }
         self.needs_switch = array[self.y] > array[self.y + 1];
-        self.reason = Reasons::Comparing;
+        self.operations.push(Operation::Compare(self.y, self.y + 1));
         false
     }
 
     fn switch(&mut self, array: &mut Vec<usize>) {
         array.swap(self.y, self.y + 1);
-        self.reason = Reasons::Switching;
+        self.operations.push(Operation::Swap(self.y, self.y + 1));
         self.needs_switch = false;
     }
 
@@ -73,12 +68,13 @@ mod tests {
         },
         util,
     };
+    use rand::thread_rng;
 
     #[test]
     fn run() {
         for _ in 0..REPETITIONS {
             let mut sorter = BubbleSort::new();
-            let mut array = util::gen_random_vector(FLOOR, CEIL, SIZE);
+            let mut array = util::gen_random_vector(FLOOR, CEIL, SIZE, &mut thread_rng());
 
             let mut expected = array.clone();
             expected.sort();
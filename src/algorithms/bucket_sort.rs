@@ -0,0 +1,280 @@
+use super::{radix_sort::Buckets, Buffer, Operation, Sorter};
+use crate::util;
+
+#[derive(PartialEq, Clone)]
+enum State {
+    Init,
+    Distributing,
+    Collecting,
+    Sorting,
+    Over,
+}
+
+/// Distributes the numbers into buckets of equal ranges, then collects each bucket back
+/// and insertion sorts it, which is quick as each bucket has only a few numbers.
+#[derive(Clone)]
+pub struct BucketSort {
+    operations: Vec<Operation>,
+    state: State,
+    buckets: Buckets,
+    min: usize,
+    /// How many distinct numbers fit between the lowest and the highest number,
+    /// which is one more than a `usize` holds when they are 0 and `usize::MAX`.
+    span: u128,
+    /// The position being read from when distributing, or written to when collecting.
+    i: usize,
+    /// The bucket being collected or sorted.
+    bucket: usize,
+    /// Where the numbers of the bucket being collected or sorted start.
+    lo: usize,
+    /// The number being inserted into the sorted numbers before it.
+    curr: usize,
+    /// The position of the number being inserted.
+    y: usize,
+    needs_switch: bool,
+    switched: bool,
+}
+
+impl BucketSort {
+    /// Returns the bucket of `value`.
+    fn bucket_of(&self, value: usize) -> usize {
+        util::scale(value - self.min, self.buckets.buckets.len(), self.span)
+    }
+
+    /// Returns the first and last numbers that go into `bucket`.
+    fn range_of(&self, bucket: usize) -> (usize, usize) {
+        let count = self.buckets.buckets.len() as u128;
+        let first = (bucket as u128 * self.span).div_ceil(count);
+        let last = ((bucket as u128 + 1) * self.span).div_ceil(count);
+        let min = self.min as u128;
+        (
+            (min + first) as usize,
+            (min + last).saturating_sub(1) as usize,
+        )
+    }
+}
+
+impl Sorter for BucketSort {
+    fn new() -> BucketSort {
+        BucketSort {
+            operations: vec![],
+            state: State::Init,
+            buckets: Buckets::new(0),
+            min: 0,
+            span: 1,
+            i: 0,
+            bucket: 0,
+            lo: 0,
+            curr: 0,
+            y: 0,
+            needs_switch: false,
+            switched: false,
+        }
+    }
+
+    fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    fn step(&mut self, array: &mut Vec<usize>) -> bool {
+        self.operations.clear();
+        self.buckets.written = None;
+        if self.needs_switch {
+            self.switch(array);
+            return false;
+        }
+        if self.modify_state(array) {
+            return true;
+        }
+        if self.state != State::Sorting {
+            self.switch(array);
+        }
+        false
+    }
+
+    fn modify_state(&mut self, array: &[usize]) -> bool {
+        if self.state == State::Init {
+            if array.len() < 2 {
+                self.state = State::Over;
+            } else {
+                self.min = array.iter().copied().min().unwrap_or(0);
+                let max = array.iter().copied().max().unwrap_or(0);
+                self.span = (max - self.min) as u128 + 1;
+                // About as many buckets as numbers in each bucket
+                let count = (array.len() as f64).sqrt().ceil() as usize;
+                self.buckets = Buckets::new(count);
+                self.state = State::Distributing;
+            }
+        }
+        if self.state == State::Distributing && self.i >= array.len() {
+            self.state = State::Collecting;
+            self.i = 0;
+        }
+        loop {
+            match self.state {
+                State::Sorting => {
+                    if self.switched && self.y > self.lo + 1 {
+                        self.y -= 1;
+                    } else {
+                        self.curr += 1;
+                        self.y = self.curr;
+                    }
+                    self.switched = false;
+                    if self.curr < self.i {
+                        self.operations.push(Operation::Compare(self.y - 1, self.y));
+                        self.needs_switch = array[self.y] < array[self.y - 1];
+                        return false;
+                    }
+                    self.bucket += 1;
+                    self.lo = self.i;
+                    self.state = State::Collecting;
+                }
+                State::Collecting => {
+                    if self.bucket >= self.buckets.buckets.len() {
+                        self.state = State::Over;
+                    } else if !self.buckets.buckets[self.bucket].is_empty() {
+                        return false;
+                    } else if self.i - self.lo > 1 {
+                        // The whole bucket has been collected, so it's sorted in place
+                        self.state = State::Sorting;
+                        self.curr = self.lo;
+                        self.operations
+                            .push(Operation::EnterRange(self.lo, self.i - 1));
+                    } else {
+                        self.bucket += 1;
+                        self.lo = self.i;
+                    }
+                }
+                State::Over => return true,
+                State::Init | State::Distributing => return false,
+            }
+        }
+    }
+
+    fn switch(&mut self, array: &mut Vec<usize>) {
+        match self.state {
+            State::Distributing => {
+                let value = array[self.i];
+                let position = self.buckets.push(self.bucket_of(value), value);
+                self.operations.push(Operation::Read(self.i));
+                self.operations.push(Operation::AuxWrite(position, value));
+                self.i += 1;
+            }
+            State::Collecting => {
+                if let Some((_, value)) = self.buckets.pop(self.bucket) {
+                    array[self.i] = value;
+                    self.operations.push(Operation::Write(self.i, value));
+                    self.i += 1;
+                }
+            }
+            State::Sorting => {
+                array.swap(self.y - 1, self.y);
+                self.operations.push(Operation::Swap(self.y - 1, self.y));
+                self.needs_switch = false;
+                self.switched = true;
+            }
+            State::Init | State::Over => {}
+        }
+    }
+
+    fn reset_state(&mut self) {
+        *self = BucketSort::new();
+    }
+
+    fn status(&self) -> Option<String> {
+        match self.state {
+            State::Distributing => Some(format!(
+                "Distributing into {} buckets",
+                self.buckets.buckets.len()
+            )),
+            State::Collecting => Some(format!("Collecting bucket {}", self.bucket)),
+            State::Sorting => Some(format!("Insertion sorting bucket {}", self.bucket)),
+            State::Init | State::Over => None,
+        }
+    }
+
+    fn buffers(&self) -> Vec<Buffer> {
+        self.buckets.buffers(|bucket| {
+            let (first, last) = self.range_of(bucket);
+            format!("{first}-{last}")
+        })
+    }
+
+    fn memory(&self) -> usize {
+        self.buckets.memory()
+    }
+
+    fn active_range(&self) -> Option<(usize, usize)> {
+        match self.state {
+            State::Sorting => Some((self.lo, self.i - 1)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BucketSort;
+    use crate::{
+        algorithms::{
+            Operation, Sorter, {CEIL, FLOOR, REPETITIONS, SIZE},
+        },
+        util,
+    };
+    use rand::thread_rng;
+
+    #[test]
+    fn run() {
+        for _ in 0..REPETITIONS {
+            let mut sorter = BucketSort::new();
+            let mut array = util::gen_random_vector(FLOOR, CEIL, SIZE, &mut thread_rng());
+            let mut expected = array.clone();
+            expected.sort();
+            sorter.run(&mut array);
+            assert_eq!(array, expected);
+        }
+    }
+
+    #[test]
+    fn buffers() {
+        let mut sorter = BucketSort::new();
+        let mut array = vec![8, 2, 6, 1, 3, 0, 7, 5, 4];
+        for _ in 0..array.len() {
+            sorter.step(&mut array);
+        }
+        let buffers = sorter.buffers();
+        let labels: Vec<&str> = buffers.iter().map(|b| b.label.as_str()).collect();
+        assert_eq!(labels, ["0-2", "3-5", "6-8"]);
+        assert_eq!(buffers[0].numbers, [2, 1, 0]);
+        assert_eq!(buffers[1].numbers, [3, 5, 4]);
+
+        // The first bucket is collected and then sorted
+        for _ in 0..3 {
+            sorter.step(&mut array);
+        }
+        assert_eq!(array[..3], [2, 1, 0]);
+        sorter.step(&mut array);
+        assert_eq!(
+            sorter.operations(),
+            [Operation::EnterRange(0, 2), Operation::Compare(0, 1)]
+        );
+        sorter.run(&mut array);
+        assert_eq!(array, [0, 1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn wide_range() {
+        let mut sorter = BucketSort::new();
+        let mut array = vec![usize::MAX, 0, usize::MAX / 2, usize::MAX - 1, 1];
+        for _ in 0..array.len() {
+            sorter.step(&mut array);
+        }
+        let buffers = sorter.buffers();
+        assert_eq!(buffers[0].numbers, [0, 1]);
+        assert_eq!(buffers[1].numbers, [usize::MAX / 2]);
+        assert_eq!(buffers[2].numbers, [usize::MAX, usize::MAX - 1]);
+        assert!(buffers[2].label.ends_with(&format!("-{}", usize::MAX)));
+        sorter.run(&mut array);
+        assert_eq!(array, [0, 1, usize::MAX / 2, usize::MAX - 1, usize::MAX]);
+    }
+}
@@ -0,0 +1,255 @@
+use super::{insertion_sort::InsertionSort, Buffer, Operation, Sorter};
+
+/// Most distinct numbers, from the lowest to the highest, that are counted.
+/// Numbers further apart are insertion sorted instead, rather than allocating a count for each.
+pub const MAX_RANGE: usize = 1 << 20;
+
+#[derive(PartialEq, Clone)]
+enum State {
+    Init,
+    Counting,
+    Summing,
+    Placing,
+    Copying,
+    /// The numbers are too far apart to be counted, so they are insertion sorted.
+    Inserting,
+    Over,
+}
+
+/// Counts how many times each number appears, sums the counts up into the positions
+/// where each number goes and places the numbers there, from last to first to keep them stable.
+/// Numbers that are further apart than [`MAX_RANGE`] are insertion sorted instead.
+#[derive(Clone)]
+pub struct CountingSort {
+    operations: Vec<Operation>,
+    state: State,
+    /// The lowest number, which is counted at the start of `counts`.
+    min: usize,
+    counts: Vec<usize>,
+    output: Vec<usize>,
+    /// The position of the array or of the counts being worked on, depending on the state.
+    i: usize,
+    /// The buffer and position written by the last step, if any.
+    written: Option<(usize, usize)>,
+    fallback: InsertionSort,
+}
+
+impl Sorter for CountingSort {
+    fn new() -> CountingSort {
+        CountingSort {
+            operations: vec![],
+            state: State::Init,
+            min: 0,
+            counts: vec![],
+            output: vec![],
+            i: 0,
+            written: None,
+            fallback: InsertionSort::new(),
+        }
+    }
+
+    fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    fn step(&mut self, array: &mut Vec<usize>) -> bool {
+        self.operations.clear();
+        self.written = None;
+        if self.modify_state(array) {
+            return true;
+        }
+        if self.state == State::Inserting {
+            let done = self.fallback.step(array);
+            self.operations
+                .extend_from_slice(self.fallback.operations());
+            return done;
+        }
+        self.switch(array);
+        false
+    }
+
+    fn modify_state(&mut self, array: &[usize]) -> bool {
+        if self.state == State::Init {
+            if array.len() < 2 {
+                self.state = State::Over;
+            } else {
+                self.min = array.iter().copied().min().unwrap_or(0);
+                let max = array.iter().copied().max().unwrap_or(0);
+                if max - self.min >= MAX_RANGE {
+                    self.state = State::Inserting;
+                } else {
+                    self.counts = vec![0; max - self.min + 1];
+                    self.output = vec![0; array.len()];
+                    self.i = 0;
+                    self.state = State::Counting;
+                }
+            }
+        }
+        if self.state == State::Counting && self.i >= array.len() {
+            self.state = State::Summing;
+            self.i = 1;
+        }
+        if self.state == State::Summing && self.i >= self.counts.len() {
+            // Placing goes backwards, so numbers that are equal keep their order
+            self.state = State::Placing;
+            self.i = array.len();
+        }
+        if self.state == State::Placing && self.i == 0 {
+            self.state = State::Copying;
+        }
+        if self.state == State::Copying && self.i >= array.len() {
+            self.state = State::Over;
+        }
+        self.state == State::Over
+    }
+
+    fn switch(&mut self, array: &mut Vec<usize>) {
+        match self.state {
+            State::Counting => {
+                let count = array[self.i] - self.min;
+                self.counts[count] += 1;
+                self.operations.push(Operation::Read(self.i));
+                self.operations
+                    .push(Operation::AuxWrite(count, self.counts[count]));
+                self.written = Some((0, count));
+                self.i += 1;
+            }
+            State::Summing => {
+                self.counts[self.i] += self.counts[self.i - 1];
+                self.operations
+                    .push(Operation::AuxWrite(self.i, self.counts[self.i]));
+                self.written = Some((0, self.i));
+                self.i += 1;
+            }
+            State::Placing => {
+                self.i -= 1;
+                let value = array[self.i];
+                let count = value - self.min;
+                self.counts[count] -= 1;
+                let position = self.counts[count];
+                self.output[position] = value;
+                self.operations.push(Operation::Read(self.i));
+                self.operations.push(Operation::AuxWrite(position, value));
+                self.written = Some((1, position));
+            }
+            State::Copying => {
+                array[self.i] = self.output[self.i];
+                self.operations
+                    .push(Operation::Write(self.i, self.output[self.i]));
+                self.i += 1;
+            }
+            State::Init | State::Inserting | State::Over => {}
+        }
+    }
+
+    fn reset_state(&mut self) {
+        *self = CountingSort::new();
+    }
+
+    fn status(&self) -> Option<String> {
+        let status = match self.state {
+            State::Counting => "Counting each number",
+            State::Summing => "Summing the counts up",
+            State::Placing => "Placing each number where its sum says",
+            State::Copying => "Copying the numbers back",
+            State::Inserting => "Insertion sorting, the numbers are too far apart to count",
+            State::Init | State::Over => return None,
+        };
+        Some(status.to_string())
+    }
+
+    fn buffers(&self) -> Vec<Buffer> {
+        if self.counts.is_empty() {
+            return vec![];
+        }
+        let highlighted = |buffer| match self.written {
+            Some((written, position)) if written == buffer => vec![position],
+            _ => vec![],
+        };
+        let counts = match self.state {
+            State::Counting => format!("Counts of {} onwards", self.min),
+            _ => format!("Prefix sums of {} onwards", self.min),
+        };
+        vec![
+            Buffer {
+                label: counts,
+                numbers: self.counts.clone(),
+                highlighted: highlighted(0),
+                ..Default::default()
+            },
+            Buffer {
+                label: "Output".to_string(),
+                numbers: self.output.clone(),
+                highlighted: highlighted(1),
+                ..Default::default()
+            },
+        ]
+    }
+
+    fn memory(&self) -> usize {
+        self.counts.len() + self.output.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CountingSort;
+    use crate::{
+        algorithms::{
+            Operation, Sorter, {CEIL, FLOOR, REPETITIONS, SIZE},
+        },
+        util,
+    };
+    use rand::thread_rng;
+
+    #[test]
+    fn run() {
+        for _ in 0..REPETITIONS {
+            let mut sorter = CountingSort::new();
+            let mut array = util::gen_random_vector(FLOOR, CEIL, SIZE, &mut thread_rng());
+            let mut expected = array.clone();
+            expected.sort();
+            sorter.run(&mut array);
+            assert_eq!(array, expected);
+        }
+    }
+
+    #[test]
+    fn buffers() {
+        let mut sorter = CountingSort::new();
+        let mut array = vec![3, 1, 3, 2];
+        for _ in 0..4 {
+            sorter.step(&mut array);
+        }
+        assert_eq!(sorter.buffers()[0].numbers, [1, 1, 2]);
+        assert_eq!(sorter.buffers()[0].highlighted, [1]);
+
+        // Summing up
+        sorter.step(&mut array);
+        sorter.step(&mut array);
+        assert_eq!(sorter.operations(), [Operation::AuxWrite(2, 4)]);
+        assert_eq!(sorter.buffers()[0].numbers, [1, 2, 4]);
+
+        // The last number goes right before where the sum of its count says
+        sorter.step(&mut array);
+        assert_eq!(
+            sorter.operations(),
+            [Operation::Read(3), Operation::AuxWrite(1, 2)]
+        );
+        assert_eq!(sorter.buffers()[1].numbers, [0, 2, 0, 0]);
+        sorter.run(&mut array);
+        assert_eq!(array, [1, 2, 3, 3]);
+    }
+
+    #[test]
+    fn wide_range() {
+        let mut sorter = CountingSort::new();
+        let mut array = vec![usize::MAX, 0, 3, usize::MAX - 1, 3];
+        sorter.step(&mut array);
+        assert_eq!(sorter.operations(), [Operation::Compare(0, 1)]);
+        assert!(sorter.status().unwrap().starts_with("Insertion sorting"));
+        assert!(sorter.buffers().is_empty());
+        sorter.run(&mut array);
+        assert_eq!(array, [0, 3, 3, usize::MAX - 1, usize::MAX]);
+    }
+}
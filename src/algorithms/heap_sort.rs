@@ -1,44 +1,65 @@
-use super::{Reasons, Sorter};
+use super::{Heap, Operation, Sorter};
 
+/// Builds a max-heap and repeatedly switches its root to the end of the array.
+#[derive(Clone)]
 pub struct HeapSort {
     index: usize,
-    special: (usize, usize),
     swapped: bool,
     root: usize,
     start: usize,
-    reason: Reasons,
+    operations: Vec<Operation>,
+    /// How many numbers are being sorted, known from the first step.
+    len: usize,
+}
+
+/// Takes a single step sifting the number at `root` down the max-heap kept from `lo` to `end`.
+/// Returns where the number was switched to, or `None` if it's already in place.
+//
+// Thanks for Pavankumar for the code that inspired our heapify: https://chercher.tech/rust/heap-sort-rust
+pub(super) fn sift_down(
+    array: &mut [usize],
+    lo: usize,
+    root: usize,
+    end: usize,
+    operations: &mut Vec<Operation>,
+) -> Option<usize> {
+    let mut child = lo + (root - lo) * 2 + 1;
+    if child > end {
+        return None;
+    }
+    if child < end {
+        operations.push(Operation::Compare(child, child + 1));
+        if array[child] < array[child + 1] {
+            child += 1;
+        }
+    }
+
+    operations.push(Operation::Compare(root, child));
+    if array[root] < array[child] {
+        array.swap(root, child);
+        operations.push(Operation::Swap(root, child));
+        return Some(child);
+    }
+    None
 }
 
 impl HeapSort {
     /// Take a single step in a heapification.
     /// Returns false when a swap happens
-
-    // Thanks for Pavankumar for the code that inspired our heapify: https://chercher.tech/rust/heap-sort-rust
     fn sift_down(&mut self, array: &mut [usize], end_index: usize) -> bool {
-        let mut child = self.root * 2 + 1;
-
-        if child > end_index {
-            return true;
-        } else {
-            if child < end_index && array[child] < array[child + 1] {
-                child += 1;
-            }
-
-            if array[self.root] < array[child] {
-                self.swap(array, self.root, child, Reasons::Comparing);
-            } else {
-                return true;
+        match sift_down(array, 0, self.root, end_index, &mut self.operations) {
+            Some(child) => {
+                self.root = child;
+                false
             }
+            None => true,
         }
-
-        false
     }
 
-    /// Swaps (a,b) in array, mark them as special, and update reason
-    fn swap(&mut self, array: &mut [usize], a: usize, b: usize, reason: Reasons) {
+    /// Swaps (a,b) in array, record the operation, and update root
+    fn swap(&mut self, array: &mut [usize], a: usize, b: usize) {
         array.swap(a, b);
-        self.reason = reason;
-        self.special = (a, b);
+        self.operations.push(Operation::Swap(a, b));
         self.root = b;
     }
 }
@@ -47,24 +68,25 @@ impl Sorter for HeapSort {
     fn new() -> Self {
         HeapSort {
             index: usize::MAX,
-            special: (usize::MAX, usize::MAX),
             swapped: false,
             root: usize::MAX,
             start: usize::MAX,
-            reason: Reasons::Comparing,
+            operations: vec![],
+            len: 0,
         }
     }
 
-    fn special(&self) -> (usize, usize) {
-        self.special
-    }
-
-    fn reason(&self) -> super::Reasons {
-        self.reason
+    fn operations(&self) -> &[Operation] {
+        &self.operations
     }
 
     fn step(&mut self, array: &mut Vec<usize>) -> bool {
+        self.operations.clear();
         let len = array.len();
+        self.len = len;
+        if len < 2 {
+            return true;
+        }
 
         // "Start" tracks initial heap construction
         if self.start == usize::MAX {
@@ -93,7 +115,6 @@ impl Sorter for HeapSort {
             return false;
         }
         // We finally can sort using "index"
-        // We use different "reasons" for swaps and heapification
         self.switch(array);
         // This function has no semantics in this algorithm
         self.modify_state(array)
@@ -114,7 +135,7 @@ impl Sorter for HeapSort {
 
         // We don't use an else because as soon as we set swapped to false we want to swap
         if !self.swapped {
-            self.swap(array, self.index, 0, Reasons::Switching);
+            self.swap(array, self.index, 0);
             self.swapped = true;
         }
     }
@@ -122,6 +143,23 @@ impl Sorter for HeapSort {
     fn reset_state(&mut self) {
         *self = HeapSort::new();
     }
+
+    fn heap(&self) -> Option<Heap> {
+        if self.root == usize::MAX || self.index == 0 {
+            return None;
+        }
+        // Until it's built the heap is the whole array, then the numbers before `index`
+        let end = if self.index == usize::MAX {
+            self.len - 1
+        } else {
+            self.index - 1
+        };
+        Some(Heap {
+            start: 0,
+            end,
+            sifting: Some(self.root),
+        })
+    }
 }
 
 #[cfg(test)]
@@ -133,12 +171,13 @@ mod tests {
         },
         util,
     };
+    use rand::thread_rng;
 
     #[test]
     fn run() {
         for _ in 0..REPETITIONS {
             let mut sorter = HeapSort::new();
-            let mut array = util::gen_random_vector(FLOOR, CEIL, SIZE);
+            let mut array = util::gen_random_vector(FLOOR, CEIL, SIZE, &mut thread_rng());
 
             let mut expected = array.clone();
             expected.sort();
@@ -148,4 +187,29 @@ mod tests {
             assert_eq!(array, expected);
         }
     }
+
+    #[test]
+    fn heap() {
+        let mut sorter = HeapSort::new();
+        let mut array = vec![1, 2, 3, 4, 5];
+        assert_eq!(sorter.heap(), None);
+
+        // Building sifts the parents down, the last one going down to its second child
+        sorter.step(&mut array);
+        assert_eq!(array, [1, 5, 3, 4, 2]);
+        let heap = sorter.heap().unwrap();
+        assert_eq!((heap.start, heap.end), (0, 4));
+        assert_eq!(heap.sifting, Some(4));
+        assert_eq!(heap.children(1), [3, 4]);
+        assert_eq!(heap.children(2), []);
+
+        // Once built, the root goes after the heap, which shrinks
+        while sorter.heap().unwrap().end == 4 {
+            sorter.step(&mut array);
+        }
+        assert_eq!(array[4], 5);
+        assert_eq!(sorter.heap().unwrap().end, 3);
+        sorter.run(&mut array);
+        assert_eq!(array, [1, 2, 3, 4, 5]);
+    }
 }
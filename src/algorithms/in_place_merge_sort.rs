@@ -0,0 +1,246 @@
+use super::{
+    top_down_merge_sort::{next_merge, Task},
+    Operation, Sorter,
+};
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+enum State {
+    /// Looking for a number of the first half that goes after the head of the second.
+    Seeking,
+    /// Looking for the end of the block of the second half that goes before it.
+    Extending,
+    /// Moving the block before it, by rotating the numbers in between.
+    Rotating,
+}
+
+/// Sorts each half of the array before merging them, like [`TopDownMergeSort`],
+/// but merges in place: the numbers of the second half that go before a number of the first
+/// are rotated into place, taking no buffer at the cost of many more swaps.
+///
+/// [`TopDownMergeSort`]: super::top_down_merge_sort::TopDownMergeSort
+#[derive(Clone)]
+pub struct InPlaceMergeSort {
+    /// The calls yet to be finished, the innermost on top.
+    stack: Vec<Task>,
+    started: bool,
+    /// First and last positions of the merge in progress, if any.
+    merging: Option<(usize, usize)>,
+    /// Head of the first half.
+    i: usize,
+    /// Head of the second half.
+    mid: usize,
+    /// End of the block of the second half that goes before `i` (exclusive).
+    j: usize,
+    /// The reversals left to rotate the block into place, the next on top.
+    reversals: Vec<(usize, usize)>,
+    state: State,
+    operations: Vec<Operation>,
+}
+
+impl Sorter for InPlaceMergeSort {
+    fn new() -> Self {
+        InPlaceMergeSort {
+            stack: vec![],
+            started: false,
+            merging: None,
+            i: 0,
+            mid: 0,
+            j: 0,
+            reversals: vec![],
+            state: State::Seeking,
+            operations: vec![],
+        }
+    }
+
+    fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    fn step(&mut self, array: &mut Vec<usize>) -> bool {
+        self.operations.clear();
+        if !self.started {
+            self.started = true;
+            if array.len() > 1 {
+                self.stack.push(Task::Sort(0, array.len() - 1));
+            }
+        }
+        if self.modify_state(array) {
+            return true;
+        }
+        self.switch(array);
+        false
+    }
+
+    fn modify_state(&mut self, _array: &[usize]) -> bool {
+        loop {
+            if let Some((_, hi)) = self.merging {
+                // A merge is over once either half runs out
+                if self.state != State::Seeking || (self.i < self.mid && self.mid <= hi) {
+                    return false;
+                }
+                self.merging = None;
+            }
+            let Some((lo, mid, hi)) = next_merge(&mut self.stack) else {
+                return true;
+            };
+            self.merging = Some((lo, hi));
+            self.operations.push(Operation::EnterRange(lo, hi));
+            self.i = lo;
+            self.mid = mid;
+        }
+    }
+
+    fn switch(&mut self, array: &mut Vec<usize>) {
+        let Some((_, hi)) = self.merging else {
+            return;
+        };
+        if self.state == State::Seeking {
+            self.operations.push(Operation::Compare(self.i, self.mid));
+            if array[self.i] <= array[self.mid] {
+                self.i += 1;
+                return;
+            }
+            self.j = self.mid + 1;
+            self.state = State::Extending;
+            return;
+        }
+        if self.state == State::Extending {
+            if self.j <= hi {
+                self.operations.push(Operation::Compare(self.i, self.j));
+                if array[self.j] < array[self.i] {
+                    self.j += 1;
+                    return;
+                }
+            }
+            // Reversing both parts and then all of them swaps their places
+            self.reversals = [
+                (self.i, self.j - 1),
+                (self.mid, self.j - 1),
+                (self.i, self.mid - 1),
+            ]
+            .into_iter()
+            .filter(|(a, b)| a < b)
+            .collect();
+            self.state = State::Rotating;
+        }
+        if let Some((a, b)) = self.reversals.pop() {
+            array.swap(a, b);
+            self.operations.push(Operation::Swap(a, b));
+            if a + 1 < b - 1 {
+                self.reversals.push((a + 1, b - 1));
+            }
+        }
+        if self.reversals.is_empty() {
+            // The number at `i` is now after the block, and was found not to go after `j`
+            self.i += self.j - self.mid + 1;
+            self.mid = self.j;
+            self.state = State::Seeking;
+        }
+    }
+
+    fn reset_state(&mut self) {
+        *self = InPlaceMergeSort::new();
+    }
+
+    fn status(&self) -> Option<String> {
+        match (self.merging, self.state) {
+            (Some(_), State::Rotating) => Some(format!(
+                "Rotating {}-{} before {}-{}",
+                self.mid,
+                self.j - 1,
+                self.i,
+                self.mid - 1
+            )),
+            _ => None,
+        }
+    }
+
+    /// The slices of the calls yet to be finished, the one being merged last.
+    fn ranges(&self) -> Vec<(usize, usize)> {
+        self.stack
+            .iter()
+            .map(Task::range)
+            .chain(self.active_range())
+            .collect()
+    }
+
+    fn active_range(&self) -> Option<(usize, usize)> {
+        self.merging
+    }
+
+    fn cursors(&self) -> Vec<(String, usize)> {
+        let Some((_, hi)) = self.merging else {
+            return vec![];
+        };
+        // Once a half runs out, its head points past it until the next merge
+        let mut cursors = vec![];
+        if self.state != State::Rotating {
+            if self.i < self.mid {
+                cursors.push(("i".to_string(), self.i));
+            }
+            if self.mid <= hi {
+                cursors.push(("mid".to_string(), self.mid));
+            }
+        }
+        if self.state == State::Extending && self.j <= hi {
+            cursors.push(("j".to_string(), self.j));
+        }
+        cursors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InPlaceMergeSort, Task};
+    use crate::{
+        algorithms::{
+            Operation, Sorter, {CEIL, FLOOR, REPETITIONS, SIZE},
+        },
+        util,
+    };
+    use rand::thread_rng;
+
+    #[test]
+    fn run() {
+        for _ in 0..REPETITIONS {
+            let mut sorter = InPlaceMergeSort::new();
+            let mut array = util::gen_random_vector(FLOOR, CEIL, SIZE, &mut thread_rng());
+
+            let mut expected = array.clone();
+            expected.sort();
+
+            let stats = sorter.run(&mut array);
+
+            assert_eq!(array, expected);
+            assert_eq!(stats.aux_memory, 0);
+            assert_eq!(stats.aux_writes, 0);
+        }
+    }
+
+    #[test]
+    fn rotation() {
+        let mut sorter = InPlaceMergeSort::new();
+        // The halves are sorted already, so the first merge is the whole array
+        let mut array = vec![3, 4, 5, 1, 2, 6];
+        sorter.stack.push(Task::Merge(0, 3, 5));
+        sorter.started = true;
+
+        sorter.step(&mut array);
+        assert_eq!(
+            sorter.operations(),
+            [Operation::EnterRange(0, 5), Operation::Compare(0, 3)]
+        );
+        // 1 and 2 go before 3, but 6 doesn't
+        sorter.step(&mut array);
+        assert_eq!(sorter.operations(), [Operation::Compare(0, 4)]);
+        sorter.step(&mut array);
+        assert_eq!(
+            sorter.operations(),
+            [Operation::Compare(0, 5), Operation::Swap(0, 2)]
+        );
+        assert_eq!(sorter.status().unwrap(), "Rotating 3-4 before 0-2");
+
+        sorter.run(&mut array);
+        assert_eq!(array, [1, 2, 3, 4, 5, 6]);
+    }
+}
@@ -1,11 +1,13 @@
-use super::{Reasons, Sorter};
+use super::{Operation, Sorter};
 
+/// Switches each number backwards until it meets a lower one.
+#[derive(Clone)]
 pub struct InsertionSort {
     x: usize,
     y: usize,
     curr: usize,
     needs_switch: bool,
-    reason: Reasons,
+    operations: Vec<Operation>,
     switched: bool,
 }
 
@@ -16,23 +18,17 @@ impl Sorter for InsertionSort {
             y: 1,
             curr: 1,
             needs_switch: false,
-            reason: Reasons::Comparing,
+            operations: vec![],
             switched: false,
         }
     }
 
-    fn special(&self) -> (usize, usize) {
-        if self.curr != 1 {
-            return (self.x, self.y);
-        }
-        (usize::MAX, usize::MAX)
-    }
-
-    fn reason(&self) -> super::Reasons {
-        self.reason
+    fn operations(&self) -> &[Operation] {
+        &self.operations
     }
 
     fn step(&mut self, array: &mut Vec<usize>) -> bool {
+        self.operations.clear();
         if self.needs_switch {
             self.switch(array)
         } else {
@@ -45,7 +41,6 @@ impl Sorter for InsertionSort {
         if self.curr >= array.len() && !self.switched {
             return true;
         }
-        self.reason = Reasons::Comparing;
         if self.switched && self.y > 1 {
             self.x -= 1;
             self.y -= 1;
@@ -56,12 +51,15 @@ impl Sorter for InsertionSort {
         }
         self.switched = false;
         self.needs_switch = self.y < array.len() && array[self.y] < array[self.x] && self.y > 0;
+        if self.y < array.len() {
+            self.operations.push(Operation::Compare(self.x, self.y));
+        }
         false
     }
 
     fn switch(&mut self, array: &mut Vec<usize>) {
         array.swap(self.y, self.x);
-        self.reason = Reasons::Switching;
+        self.operations.push(Operation::Swap(self.x, self.y));
         self.needs_switch = false;
         self.switched = true;
     }
@@ -80,12 +78,13 @@ mod tests {
         },
         util,
     };
+    use rand::thread_rng;
 
     #[test]
     fn run() {
         for _ in 0..REPETITIONS {
             let mut sorter = InsertionSort::new();
-            let mut array = util::gen_random_vector(FLOOR, CEIL, SIZE);
+            let mut array = util::gen_random_vector(FLOOR, CEIL, SIZE, &mut thread_rng());
             let mut expected = array.clone();
             expected.sort();
             sorter.run(&mut array);
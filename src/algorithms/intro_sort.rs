@@ -0,0 +1,576 @@
+use super::{
+    heap_sort::sift_down,
+    quick_sort::{median, ninther},
+    Heap, Operation, Sorter,
+};
+
+/// Partitions of at most this many numbers are insertion sorted. Real implementations use 16
+/// or more, see [`MIN_MERGE`](super::tim_sort::MIN_MERGE) for why it's smaller.
+const INSERTION_SIZE: usize = 6;
+/// Partitions longer than this pick their pivot with a ninther, when defeating patterns.
+const NINTHER_SIZE: usize = 32;
+/// How many switches a partial insertion sort may make before giving up.
+const PARTIAL_INSERTION_LIMIT: usize = 8;
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+enum State {
+    Init,
+    /// Picking the next partition and how to sort it.
+    Next,
+    Pivoting,
+    /// Switching the pivot to the end of the partition.
+    MovingPivot,
+    Partitioning,
+    /// Switching a number that goes before the pivot to the left side.
+    PartitionSwap,
+    /// Switching the pivot between the sides.
+    Placing,
+    /// Switching a few numbers of an unbalanced partition around.
+    BreakingPatterns,
+    Inserting,
+    InsertSwap,
+    HeapBuilding,
+    /// Switching the root of the heap to its end.
+    HeapSwapping,
+    HeapSifting,
+    Over,
+}
+
+/// A partition waiting to be sorted.
+#[derive(PartialEq, Debug, Clone, Copy)]
+struct Task {
+    lo: usize,
+    hi: usize,
+    /// How many more levels of quicksort (or unbalanced partitions) are allowed before heapsort.
+    depth: usize,
+    /// Whether nothing is left of the partition, so there's no earlier pivot to compare to.
+    leftmost: bool,
+    /// Whether to try a partial insertion sort first, as the partition looks sorted.
+    partial: bool,
+}
+
+/// Quicksort with a median-of-three pivot that insertion sorts small partitions
+/// and heapsorts the ones reached past a depth limit, so it never degrades to quadratic time.
+///
+/// Its [pattern-defeating](IntroSort::pattern_defeating) variant, after pdqsort, only heapsorts
+/// after too many unbalanced partitions, breaking the patterns that cause them, takes numbers
+/// equal to an earlier pivot out at once and lets insertion sort finish partitions that look sorted.
+#[derive(Clone)]
+pub struct IntroSort {
+    operations: Vec<Operation>,
+    state: State,
+    pattern_defeating: bool,
+    tasks: Vec<Task>,
+    /// The partition being sorted.
+    task: Task,
+    pivot: usize,
+    /// Whether the partition separates the numbers equal to an earlier pivot, instead of the lower ones.
+    equal_left: bool,
+    /// Numbers before `i` go before the pivot, `j` is the next number to be looked at.
+    i: usize,
+    j: usize,
+    /// Whether partitioning switched any number, which it doesn't for sorted numbers.
+    swapped_any: bool,
+    pattern_swaps: Vec<(usize, usize)>,
+    /// The number being inserted, and its position.
+    curr: usize,
+    y: usize,
+    /// Switches made by the current insertion sort.
+    moves: usize,
+    /// The parent being sifted down while building the heap.
+    heap_start: usize,
+    /// The last position still in the heap.
+    heap_end: usize,
+    root: usize,
+}
+
+impl IntroSort {
+    /// Creates the pattern-defeating variant, in the style of pdqsort.
+    pub fn pattern_defeating() -> IntroSort {
+        IntroSort {
+            pattern_defeating: true,
+            ..IntroSort::new()
+        }
+    }
+
+    /// Returns the first child of `root` in the heap of the current partition.
+    fn child(&self, root: usize) -> usize {
+        self.task.lo + (root - self.task.lo) * 2 + 1
+    }
+
+    /// Picks the next parent to sift down, or starts taking the heap apart after the last one.
+    fn next_parent(&mut self) {
+        if self.heap_start == self.task.lo {
+            self.state = State::HeapSwapping;
+        } else {
+            self.heap_start -= 1;
+            self.root = self.heap_start;
+        }
+    }
+
+    /// Queues the sides of the partition, now that the pivot is at `i`.
+    fn place(&mut self) {
+        let Task {
+            lo,
+            hi,
+            depth,
+            leftmost,
+            ..
+        } = self.task;
+        let p = self.i;
+        let side = |lo, hi, depth, leftmost, partial| Task {
+            lo,
+            hi,
+            depth,
+            leftmost,
+            partial,
+        };
+        self.state = State::Next;
+        if !self.pattern_defeating {
+            if hi > p + 1 {
+                self.tasks.push(side(p + 1, hi, depth - 1, false, false));
+            }
+            if p > lo + 1 {
+                self.tasks.push(side(lo, p - 1, depth - 1, leftmost, false));
+            }
+            return;
+        }
+        // Only the equal numbers went left, so they are done
+        if self.equal_left {
+            if hi > p + 1 {
+                self.tasks.push(side(p + 1, hi, depth, false, false));
+            }
+            return;
+        }
+
+        let (left, right) = (p - lo, hi - p);
+        let len = hi - lo + 1;
+        let unbalanced = left < len / 8 || right < len / 8;
+        let mut depth = depth;
+        if unbalanced {
+            depth -= 1;
+            if depth > 0 {
+                if left >= INSERTION_SIZE {
+                    self.pattern_swaps
+                        .extend([(lo, lo + left / 4), (p - 1, p - left / 4)]);
+                }
+                if right >= INSERTION_SIZE {
+                    self.pattern_swaps
+                        .extend([(p + 1, p + 1 + right / 4), (hi, hi - right / 4)]);
+                }
+            }
+        }
+        let partial = !unbalanced && !self.swapped_any;
+        if right > 1 {
+            self.tasks.push(side(p + 1, hi, depth, false, partial));
+        }
+        if left > 1 {
+            self.tasks.push(side(lo, p - 1, depth, leftmost, partial));
+        }
+        if !self.pattern_swaps.is_empty() {
+            self.state = State::BreakingPatterns;
+        }
+    }
+}
+
+impl Sorter for IntroSort {
+    fn new() -> IntroSort {
+        let task = Task {
+            lo: 0,
+            hi: 0,
+            depth: 0,
+            leftmost: true,
+            partial: false,
+        };
+        IntroSort {
+            operations: vec![],
+            state: State::Init,
+            pattern_defeating: false,
+            tasks: vec![],
+            task,
+            pivot: 0,
+            equal_left: false,
+            i: 0,
+            j: 0,
+            swapped_any: false,
+            pattern_swaps: vec![],
+            curr: 0,
+            y: 0,
+            moves: 0,
+            heap_start: 0,
+            heap_end: 0,
+            root: 0,
+        }
+    }
+
+    fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    fn step(&mut self, array: &mut Vec<usize>) -> bool {
+        self.operations.clear();
+        if self.modify_state(array) {
+            return true;
+        }
+        self.switch(array);
+        false
+    }
+
+    fn modify_state(&mut self, array: &[usize]) -> bool {
+        loop {
+            match self.state {
+                State::Init => {
+                    let n = array.len();
+                    if n < 2 {
+                        self.state = State::Over;
+                        continue;
+                    }
+                    // Balanced partitions halve the numbers, so they never get this deep
+                    let levels = n.ilog2() as usize;
+                    let depth = if self.pattern_defeating {
+                        levels
+                    } else {
+                        2 * levels
+                    };
+                    self.tasks.push(Task {
+                        lo: 0,
+                        hi: n - 1,
+                        depth,
+                        leftmost: true,
+                        partial: false,
+                    });
+                    self.state = State::Next;
+                }
+                State::Next => {
+                    let Some(task) = self.tasks.pop() else {
+                        self.state = State::Over;
+                        continue;
+                    };
+                    self.task = task;
+                    self.operations
+                        .push(Operation::EnterRange(task.lo, task.hi));
+                    if task.partial || task.hi - task.lo < INSERTION_SIZE {
+                        self.curr = task.lo + 1;
+                        self.y = self.curr;
+                        self.moves = 0;
+                        self.state = State::Inserting;
+                    } else if task.depth == 0 {
+                        // The last parent is halfway through, as children are twice as far
+                        let len = task.hi - task.lo + 1;
+                        self.heap_start = task.lo + len / 2 - 1;
+                        self.root = self.heap_start;
+                        self.heap_end = task.hi;
+                        self.state = State::HeapBuilding;
+                    } else {
+                        self.state = State::Pivoting;
+                    }
+                }
+                State::MovingPivot => {
+                    if self.pivot != self.task.hi {
+                        return false;
+                    }
+                    self.state = State::Partitioning;
+                }
+                State::Partitioning => {
+                    if self.j < self.task.hi {
+                        return false;
+                    }
+                    self.state = State::Placing;
+                }
+                State::Placing => {
+                    if self.i != self.task.hi {
+                        return false;
+                    }
+                    self.place();
+                }
+                State::BreakingPatterns => {
+                    if !self.pattern_swaps.is_empty() {
+                        return false;
+                    }
+                    self.state = State::Next;
+                }
+                State::Inserting => {
+                    if self.y <= self.task.lo {
+                        self.curr += 1;
+                        self.y = self.curr;
+                    }
+                    if self.curr > self.task.hi {
+                        self.state = State::Next;
+                    } else if self.task.partial
+                        && self.y == self.curr
+                        && self.moves > PARTIAL_INSERTION_LIMIT
+                    {
+                        // Not as sorted as it looked, so it's sorted the usual way
+                        self.tasks.push(Task {
+                            partial: false,
+                            ..self.task
+                        });
+                        self.state = State::Next;
+                    } else {
+                        return false;
+                    }
+                }
+                State::HeapBuilding => {
+                    if self.child(self.root) <= self.task.hi {
+                        return false;
+                    }
+                    self.next_parent();
+                }
+                State::HeapSwapping => {
+                    if self.heap_end > self.task.lo {
+                        return false;
+                    }
+                    self.state = State::Next;
+                }
+                State::HeapSifting => {
+                    if self.child(self.root) <= self.heap_end {
+                        return false;
+                    }
+                    self.state = State::HeapSwapping;
+                }
+                State::Pivoting | State::PartitionSwap | State::InsertSwap => return false,
+                State::Over => return true,
+            }
+        }
+    }
+
+    fn switch(&mut self, array: &mut Vec<usize>) {
+        let Task { lo, hi, .. } = self.task;
+        match self.state {
+            State::Pivoting => {
+                self.pivot = if self.pattern_defeating && hi - lo >= NINTHER_SIZE {
+                    ninther(lo, hi, array, &mut self.operations)
+                } else {
+                    median(lo, (lo + hi) / 2, hi, array, &mut self.operations)
+                };
+                self.operations.push(Operation::MarkPivot(self.pivot));
+                // Nothing here is lower than the pivot before it, so if this pivot
+                // is no higher than it, it's equal and so are the numbers not higher than it
+                self.equal_left = false;
+                if self.pattern_defeating && !self.task.leftmost {
+                    self.operations.push(Operation::Compare(lo - 1, self.pivot));
+                    self.equal_left = array[lo - 1] >= array[self.pivot];
+                }
+                (self.i, self.j) = (lo, lo);
+                self.swapped_any = false;
+                self.state = State::MovingPivot;
+            }
+            State::MovingPivot => {
+                array.swap(self.pivot, hi);
+                self.operations.push(Operation::Swap(self.pivot, hi));
+                self.pivot = hi;
+                self.operations.push(Operation::MarkPivot(hi));
+                self.state = State::Partitioning;
+            }
+            State::Partitioning => {
+                self.operations.push(Operation::Compare(self.j, hi));
+                self.operations.push(Operation::MarkPivot(hi));
+                let goes_left = if self.equal_left {
+                    array[self.j] <= array[hi]
+                } else {
+                    array[self.j] < array[hi]
+                };
+                if goes_left {
+                    if self.i != self.j {
+                        self.state = State::PartitionSwap;
+                        return;
+                    }
+                    self.i += 1;
+                }
+                self.j += 1;
+            }
+            State::PartitionSwap => {
+                array.swap(self.i, self.j);
+                self.operations.push(Operation::Swap(self.i, self.j));
+                self.operations.push(Operation::MarkPivot(hi));
+                self.i += 1;
+                self.j += 1;
+                self.swapped_any = true;
+                self.state = State::Partitioning;
+            }
+            State::Placing => {
+                array.swap(self.i, hi);
+                self.operations.push(Operation::Swap(self.i, hi));
+                self.operations.push(Operation::MarkPivot(self.i));
+                self.place();
+            }
+            State::BreakingPatterns => {
+                if let Some((a, b)) = self.pattern_swaps.pop() {
+                    array.swap(a, b);
+                    self.operations.push(Operation::Swap(a, b));
+                }
+            }
+            State::Inserting => {
+                self.operations.push(Operation::Compare(self.y - 1, self.y));
+                if array[self.y] < array[self.y - 1] {
+                    self.state = State::InsertSwap;
+                } else {
+                    self.curr += 1;
+                    self.y = self.curr;
+                }
+            }
+            State::InsertSwap => {
+                array.swap(self.y - 1, self.y);
+                self.operations.push(Operation::Swap(self.y - 1, self.y));
+                self.y -= 1;
+                self.moves += 1;
+                self.state = State::Inserting;
+            }
+            State::HeapBuilding => {
+                match sift_down(array, lo, self.root, hi, &mut self.operations) {
+                    Some(child) => self.root = child,
+                    None => self.next_parent(),
+                }
+            }
+            State::HeapSwapping => {
+                array.swap(lo, self.heap_end);
+                self.operations.push(Operation::Swap(lo, self.heap_end));
+                self.heap_end -= 1;
+                self.root = lo;
+                self.state = State::HeapSifting;
+            }
+            State::HeapSifting => {
+                let end = self.heap_end;
+                match sift_down(array, lo, self.root, end, &mut self.operations) {
+                    Some(child) => self.root = child,
+                    None => self.state = State::HeapSwapping,
+                }
+            }
+            State::Init | State::Next | State::Over => {}
+        }
+    }
+
+    fn reset_state(&mut self) {
+        *self = IntroSort {
+            pattern_defeating: self.pattern_defeating,
+            ..IntroSort::new()
+        };
+    }
+
+    fn status(&self) -> Option<String> {
+        let Task {
+            lo,
+            hi,
+            depth,
+            partial,
+            ..
+        } = self.task;
+        let status = match self.state {
+            State::Pivoting
+            | State::MovingPivot
+            | State::Partitioning
+            | State::PartitionSwap
+            | State::Placing => {
+                if self.equal_left {
+                    format!("Taking out the numbers of {lo}-{hi} equal to the last pivot")
+                } else if self.pattern_defeating {
+                    format!("Quicksorting {lo}-{hi}, {depth} unbalanced partitions from heapsort")
+                } else {
+                    format!("Quicksorting {lo}-{hi}, {depth} levels from heapsort")
+                }
+            }
+            State::BreakingPatterns => {
+                format!("Breaking patterns after an unbalanced partition of {lo}-{hi}")
+            }
+            State::Inserting | State::InsertSwap if partial => {
+                format!("Trying to insertion sort {lo}-{hi}, which looks sorted")
+            }
+            State::Inserting | State::InsertSwap => format!("Insertion sorting {lo}-{hi}"),
+            State::HeapBuilding | State::HeapSwapping | State::HeapSifting => {
+                format!("Heapsorting {lo}-{hi}, as quicksort went too deep")
+            }
+            State::Init | State::Next | State::Over => return None,
+        };
+        Some(status)
+    }
+
+    fn ranges(&self) -> Vec<(usize, usize)> {
+        let mut ranges: Vec<(usize, usize)> =
+            self.tasks.iter().map(|task| (task.lo, task.hi)).collect();
+        ranges.extend(self.active_range());
+        ranges
+    }
+
+    fn active_range(&self) -> Option<(usize, usize)> {
+        match self.state {
+            State::Init | State::Next | State::Over => None,
+            _ => Some((self.task.lo, self.task.hi)),
+        }
+    }
+
+    fn heap(&self) -> Option<Heap> {
+        let (end, sifting) = match self.state {
+            State::HeapBuilding => (self.task.hi, Some(self.root)),
+            State::HeapSifting => (self.heap_end, Some(self.root)),
+            State::HeapSwapping => (self.heap_end, None),
+            _ => return None,
+        };
+        Some(Heap {
+            start: self.task.lo,
+            end,
+            sifting,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IntroSort;
+    use crate::{
+        algorithms::{
+            Sorter, {CEIL, FLOOR, REPETITIONS, SIZE},
+        },
+        util,
+    };
+    use rand::thread_rng;
+
+    #[test]
+    fn run() {
+        for size in [SIZE, 200] {
+            for _ in 0..REPETITIONS {
+                for mut sorter in [IntroSort::new(), IntroSort::pattern_defeating()] {
+                    let mut array = util::gen_random_vector(FLOOR, CEIL, size, &mut thread_rng());
+                    let mut expected = array.clone();
+                    expected.sort();
+                    sorter.run(&mut array);
+                    assert_eq!(array, expected);
+                }
+            }
+        }
+    }
+
+    /// Returns every status `sorter` goes through while sorting `array`.
+    fn statuses(mut sorter: IntroSort, mut array: Vec<usize>) -> Vec<String> {
+        let mut statuses = vec![];
+        while !sorter.step(&mut array) {
+            statuses.extend(sorter.status());
+        }
+        assert!(array.windows(2).all(|w| w[0] <= w[1]));
+        statuses
+    }
+
+    #[test]
+    fn heapsort_fallback() {
+        // Equal numbers all go to one side, so every partition is as unbalanced as it gets
+        let array = vec![7; 64];
+        let intro = statuses(IntroSort::new(), array.clone());
+        assert!(intro.iter().any(|s| s.starts_with("Heapsorting")));
+
+        let pdq = statuses(IntroSort::pattern_defeating(), array);
+        assert!(pdq.iter().any(|s| s.starts_with("Taking out")));
+        assert!(!pdq.iter().any(|s| s.starts_with("Heapsorting")));
+    }
+
+    #[test]
+    fn sorted() {
+        let array: Vec<usize> = (0..64).collect();
+        let mut intro = IntroSort::new();
+        let mut pdq = IntroSort::pattern_defeating();
+        let intro_steps = intro.run(&mut array.clone()).steps;
+        let pdq_steps = pdq.run(&mut array.clone()).steps;
+        assert!(pdq_steps < intro_steps);
+        assert!(statuses(pdq, array)
+            .iter()
+            .any(|s| s.starts_with("Trying to insertion sort")));
+    }
+}
@@ -1,8 +1,8 @@
 use std::cmp::min;
 
-use super::{Reasons, Sorter};
+use super::{Buffer, Operation, Sorter};
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone)]
 enum State {
     Init,
     Comparing,
@@ -10,17 +10,20 @@ enum State {
     Over,
 }
 
+/// Merges slices of size 1, 2, 4... using an auxiliary buffer.
+#[derive(Clone)]
 pub struct MergeSort {
     power: usize,
     slice: usize,
-    special: (usize, usize),
-    reason: Reasons,
+    operations: Vec<Operation>,
     state: State,
     temp: Vec<usize>,
     i: usize,
     j: usize,
     k: usize,
     merge_tracker: usize,
+    /// How many numbers are being sorted, known from the first step.
+    len: usize,
 }
 
 impl Sorter for MergeSort {
@@ -28,27 +31,28 @@ impl Sorter for MergeSort {
         MergeSort {
             power: 1,
             slice: usize::MAX,
-            reason: Reasons::Comparing,
-            special: (usize::MAX, usize::MAX),
+            operations: vec![],
             state: State::Init,
             temp: vec![],
             i: usize::MAX,
             j: usize::MAX,
             k: usize::MAX,
             merge_tracker: 0,
+            len: 0,
         }
     }
 
-    fn special(&self) -> (usize, usize) {
-        self.special
-    }
-
-    fn reason(&self) -> Reasons {
-        self.reason
+    fn operations(&self) -> &[Operation] {
+        &self.operations
     }
 
     fn step(&mut self, array: &mut Vec<usize>) -> bool {
+        self.operations.clear();
         let size: usize = array.len();
+        self.len = size;
+        if size < 2 {
+            return true;
+        }
         if self.slice == usize::MAX {
             self.slice = 0;
         }
@@ -80,11 +84,12 @@ impl Sorter for MergeSort {
             self.j = self.slice + self.power;
             self.k = self.slice;
             self.state = State::Comparing;
+            self.operations
+                .push(Operation::EnterRange(self.slice, end_of_slice));
         }
         if self.state == State::Comparing {
             if self.i < self.slice + self.power && self.j <= end_of_slice {
-                self.special = (self.i, self.j);
-                self.reason = Reasons::Comparing;
+                self.operations.push(Operation::Compare(self.i, self.j));
                 if array[self.i] < array[self.j] {
                     self.temp[self.k] = array[self.i];
                     self.i += 1;
@@ -92,10 +97,15 @@ impl Sorter for MergeSort {
                     self.temp[self.k] = array[self.j];
                     self.j += 1;
                 }
+                self.operations
+                    .push(Operation::AuxWrite(self.k, self.temp[self.k]));
                 self.k += 1;
             } else {
                 while self.i < array.len() && self.i < self.slice + self.power {
                     self.temp[self.k] = array[self.i];
+                    self.operations.push(Operation::Read(self.i));
+                    self.operations
+                        .push(Operation::AuxWrite(self.k, self.temp[self.k]));
                     self.k += 1;
                     self.i += 1;
                 }
@@ -104,9 +114,11 @@ impl Sorter for MergeSort {
             }
         }
         if self.state == State::Merging {
-            self.special = (self.merge_tracker, self.merge_tracker);
-            self.reason = Reasons::Switching;
             array[self.merge_tracker] = self.temp[self.merge_tracker];
+            self.operations.push(Operation::Write(
+                self.merge_tracker,
+                self.temp[self.merge_tracker],
+            ));
             if self.merge_tracker >= end_of_slice {
                 self.state = State::Over;
             } else {
@@ -118,15 +130,222 @@ impl Sorter for MergeSort {
     fn reset_state(&mut self) {
         *self = MergeSort::new();
     }
+
+    /// The slices already merged in this pass, followed by the ones still to be.
+    fn ranges(&self) -> Vec<(usize, usize)> {
+        let Some((slice, _)) = self.active_range() else {
+            return vec![];
+        };
+        let merged = (0..slice).step_by(2 * self.power);
+        let unmerged = (slice..self.len).step_by(self.power);
+        merged
+            .map(|lo| (lo, lo + 2 * self.power - 1))
+            .chain(unmerged.map(|lo| (lo, min(lo + self.power, self.len) - 1)))
+            .collect()
+    }
+
+    fn active_range(&self) -> Option<(usize, usize)> {
+        if self.slice == usize::MAX || self.power >= self.len {
+            return None;
+        }
+        let end_of_slice = min(self.slice + 2 * self.power, self.len) - 1;
+        Some((self.slice, end_of_slice))
+    }
+
+    /// The buffer lines up with the array, showing only the numbers of the slice
+    /// merged so far, which are then written back.
+    fn buffers(&self) -> Vec<Buffer> {
+        let Some((slice, end_of_slice)) = self.active_range() else {
+            return vec![];
+        };
+        let (merged, k) = match self.state {
+            State::Comparing => (self.k, self.k),
+            State::Merging => (end_of_slice + 1, self.merge_tracker),
+            State::Init | State::Over => (end_of_slice + 1, end_of_slice + 1),
+        };
+        let numbers = (0..self.len)
+            .map(|x| {
+                if (slice..merged).contains(&x) {
+                    self.temp[x]
+                } else {
+                    0
+                }
+            })
+            .collect();
+        let highlighted = self
+            .operations
+            .iter()
+            .filter_map(|operation| match operation {
+                Operation::AuxWrite(k, _) => Some(*k),
+                _ => None,
+            })
+            .collect();
+        let cursors = if k <= end_of_slice {
+            vec![("k".to_string(), k)]
+        } else {
+            vec![]
+        };
+        vec![Buffer {
+            label: "Merge buffer".to_string(),
+            numbers,
+            highlighted,
+            cursors,
+        }]
+    }
+
+    /// The buffer is as long as the array for as long as there are slices to merge.
+    fn memory(&self) -> usize {
+        self.active_range().map_or(0, |_| self.len)
+    }
+
+    fn cursors(&self) -> Vec<(String, usize)> {
+        let Some((slice, end_of_slice)) = self.active_range() else {
+            return vec![];
+        };
+        if self.state != State::Comparing {
+            return vec![];
+        }
+        let mut cursors = vec![];
+        if self.i < slice + self.power {
+            cursors.push(("i".to_string(), self.i));
+        }
+        if self.j <= end_of_slice {
+            cursors.push(("j".to_string(), self.j));
+        }
+        cursors
+    }
+}
+
+/// A merge of the sorted slices from `lo` to `mid - 1` and from `mid` to `hi`,
+/// through a buffer only as long as both, which is then written back.
+#[derive(Clone)]
+pub(super) struct Merge {
+    pub(super) lo: usize,
+    mid: usize,
+    pub(super) hi: usize,
+    /// Heads of the slices being merged.
+    i: usize,
+    j: usize,
+    temp: Vec<usize>,
+    /// How many numbers of the buffer have been written back.
+    written: usize,
+}
+
+impl Merge {
+    pub(super) fn new(lo: usize, mid: usize, hi: usize) -> Self {
+        Merge {
+            lo,
+            mid,
+            hi,
+            i: lo,
+            j: mid,
+            temp: Vec::with_capacity(hi - lo + 1),
+            written: 0,
+        }
+    }
+
+    /// Moves the number at `index` to the end of the buffer.
+    fn take(&mut self, array: &[usize], index: usize, operations: &mut Vec<Operation>) {
+        self.temp.push(array[index]);
+        let k = self.lo + self.temp.len() - 1;
+        operations.push(Operation::AuxWrite(k, array[index]));
+    }
+
+    /// Whether every number is in the buffer, ready to be written back.
+    fn merged(&self) -> bool {
+        self.temp.len() == self.hi - self.lo + 1
+    }
+
+    /// Takes a single step, either moving the lower head to the buffer or writing a number back.
+    /// Returns true once every number has been written back.
+    pub(super) fn step(&mut self, array: &mut [usize], operations: &mut Vec<Operation>) -> bool {
+        if self.temp.is_empty() {
+            operations.push(Operation::EnterRange(self.lo, self.hi));
+        }
+        if !self.merged() {
+            if self.i < self.mid && self.j <= self.hi {
+                operations.push(Operation::Compare(self.i, self.j));
+                // Taking from the left on ties keeps the sort stable
+                if array[self.i] <= array[self.j] {
+                    self.take(array, self.i, operations);
+                    self.i += 1;
+                } else {
+                    self.take(array, self.j, operations);
+                    self.j += 1;
+                }
+                return false;
+            }
+            // One of the slices ran out, so the rest of the other goes in at once
+            for index in (self.i..self.mid).chain(self.j..=self.hi) {
+                operations.push(Operation::Read(index));
+                self.take(array, index, operations);
+            }
+            self.i = self.mid;
+            self.j = self.hi + 1;
+        }
+        let k = self.lo + self.written;
+        array[k] = self.temp[self.written];
+        operations.push(Operation::Write(k, array[k]));
+        self.written += 1;
+        self.written == self.temp.len()
+    }
+
+    /// Returns the buffer lined up with an array of `len` numbers, with the numbers written
+    /// to it by `operations` highlighted and the position it's written to or read from pointed at.
+    pub(super) fn buffer(&self, len: usize, operations: &[Operation]) -> Buffer {
+        let mut numbers = vec![0; len];
+        numbers[self.lo..self.lo + self.temp.len()].copy_from_slice(&self.temp);
+        let highlighted = operations
+            .iter()
+            .filter_map(|operation| match operation {
+                Operation::AuxWrite(k, _) => Some(*k),
+                _ => None,
+            })
+            .collect();
+        let k = if self.merged() {
+            self.lo + self.written
+        } else {
+            self.lo + self.temp.len()
+        };
+        Buffer {
+            label: "Merge buffer".to_string(),
+            numbers,
+            highlighted,
+            // Once every number is in the buffer, nothing is left to point at before writing back
+            cursors: if k <= self.hi {
+                vec![("k".to_string(), k)]
+            } else {
+                vec![]
+            },
+        }
+    }
+
+    /// Returns the heads of the slices still being merged.
+    pub(super) fn cursors(&self) -> Vec<(String, usize)> {
+        let mut cursors = vec![];
+        if self.i < self.mid {
+            cursors.push(("i".to_string(), self.i));
+        }
+        if self.j <= self.hi {
+            cursors.push(("j".to_string(), self.j));
+        }
+        cursors
+    }
+
+    /// Returns how many numbers the buffer is made to hold.
+    pub(super) fn memory(&self) -> usize {
+        self.hi - self.lo + 1
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use rand::thread_rng;
 
-    use super::MergeSort;
+    use super::{Merge, MergeSort};
     use crate::{
         algorithms::{
-            Sorter, {CEIL, FLOOR, REPETITIONS, SIZE},
+            Operation, Sorter, {CEIL, FLOOR, REPETITIONS, SIZE},
         },
         util,
     };
@@ -135,7 +354,7 @@ mod tests {
     fn run() {
         for _ in 0..REPETITIONS {
             let mut sorter = MergeSort::new();
-            let mut array = util::gen_random_vector(FLOOR, CEIL, SIZE);
+            let mut array = util::gen_random_vector(FLOOR, CEIL, SIZE, &mut thread_rng());
 
             let mut expected = array.clone();
             expected.sort();
@@ -145,4 +364,86 @@ mod tests {
             assert_eq!(array, expected);
         }
     }
+
+    #[test]
+    fn memory() {
+        let mut sorter = MergeSort::new();
+        let mut array = vec![4, 3, 2, 1, 0];
+        assert_eq!(sorter.memory(), 0);
+        sorter.step(&mut array);
+        assert_eq!(sorter.memory(), array.len());
+        let stats = sorter.run(&mut array);
+        assert_eq!(stats.aux_memory, array.len());
+        assert_eq!(sorter.memory(), 0);
+    }
+
+    #[test]
+    fn ranges() {
+        let mut sorter = MergeSort::new();
+        let mut array = vec![4, 3, 2, 1, 0];
+        assert_eq!(sorter.active_range(), None);
+        sorter.step(&mut array);
+        assert_eq!(sorter.active_range(), Some((0, 1)));
+        assert_eq!(sorter.ranges(), [(0, 0), (1, 1), (2, 2), (3, 3), (4, 4)]);
+
+        // Into the second pair of the first pass
+        while sorter.active_range() == Some((0, 1)) {
+            sorter.step(&mut array);
+        }
+        assert_eq!(sorter.active_range(), Some((2, 3)));
+        assert_eq!(sorter.ranges(), [(0, 1), (2, 2), (3, 3), (4, 4)]);
+    }
+
+    #[test]
+    fn buffers() {
+        let mut sorter = MergeSort::new();
+        let mut array = vec![3, 1, 2, 0];
+        assert!(sorter.buffers().is_empty());
+
+        // The smaller head goes into the buffer, lined up with where it'll be written back
+        sorter.step(&mut array);
+        let buffer = &sorter.buffers()[0];
+        assert_eq!(buffer.numbers, [1, 0, 0, 0]);
+        assert_eq!(buffer.highlighted, [0]);
+        assert_eq!(buffer.cursors, [("k".to_string(), 1)]);
+        assert_eq!(sorter.cursors(), [("i".to_string(), 0)]);
+
+        // Then the rest of the pair is drained and written back
+        sorter.step(&mut array);
+        assert_eq!(array, [1, 1, 2, 0]);
+        let buffer = &sorter.buffers()[0];
+        assert_eq!(buffer.numbers, [1, 3, 0, 0]);
+        assert_eq!(buffer.cursors, [("k".to_string(), 1)]);
+        assert!(sorter.cursors().is_empty());
+    }
+
+    #[test]
+    fn merge() {
+        let mut array = vec![1, 4, 2, 3];
+        let mut operations = vec![];
+        let mut merge = Merge::new(0, 2, 3);
+        assert!(!merge.step(&mut array, &mut operations));
+        assert_eq!(
+            operations,
+            [
+                Operation::EnterRange(0, 3),
+                Operation::Compare(0, 2),
+                Operation::AuxWrite(0, 1)
+            ]
+        );
+        assert_eq!(
+            merge.cursors(),
+            [("i".to_string(), 1), ("j".to_string(), 2)]
+        );
+
+        // Only the numbers merged so far are in the buffer
+        merge.step(&mut array, &mut operations);
+        let buffer = merge.buffer(6, &[]);
+        assert_eq!(buffer.numbers, [1, 2, 0, 0, 0, 0]);
+        assert_eq!(buffer.cursors, [("k".to_string(), 2)]);
+
+        while !merge.step(&mut array, &mut operations) {}
+        assert_eq!(array, [1, 2, 3, 4]);
+        assert_eq!(merge.memory(), 4);
+    }
 }
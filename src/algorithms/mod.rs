@@ -1,33 +1,174 @@
+/// Shuffling until sorted.
 pub mod bogo_sort;
+/// Switching adjacent numbers until sorted.
 pub mod bubble_sort;
+/// Distributing numbers into buckets of equal ranges and sorting each bucket.
+pub mod bucket_sort;
+/// Counting each number to find out where it goes.
+pub mod counting_sort;
+/// Repeatedly taking the highest number out of a binary heap.
 pub mod heap_sort;
+/// Rotating numbers into place, merging without a buffer.
+pub mod in_place_merge_sort;
+/// Inserting each number into the sorted numbers before it.
 pub mod insertion_sort;
+/// Quicksort falling back to heapsort and insertion sort.
+pub mod intro_sort;
+/// Bottom-up merging of ever larger slices.
 pub mod merge_sort;
+/// Merging the runs found in the array, pass after pass.
+pub mod natural_merge_sort;
+/// Applying fixed layers of comparators, such as bitonic sort.
+pub mod network_sort;
+/// Settings of the algorithms that can be tuned.
+pub mod options;
+/// Partitioning around a median-of-three pivot.
 pub mod quick_sort;
+/// Distributing numbers into buckets, one digit at a time.
+pub mod radix_sort;
+/// Repeatedly selecting the lowest of the unsorted numbers.
 pub mod selection_sort;
+/// Insertion sorting numbers a gap apart, for ever smaller gaps.
+pub mod shell_sort;
+/// Counting the operations performed.
+pub mod stats;
+/// Merging natural runs, galloping through the ones that keep winning.
+pub mod tim_sort;
+/// Recursively merging the sorted halves of the array.
+pub mod top_down_merge_sort;
+
+use self::{
+    bogo_sort::BogoSort,
+    bubble_sort::BubbleSort,
+    bucket_sort::BucketSort,
+    counting_sort::CountingSort,
+    heap_sort::HeapSort,
+    in_place_merge_sort::InPlaceMergeSort,
+    insertion_sort::InsertionSort,
+    intro_sort::IntroSort,
+    merge_sort::MergeSort,
+    natural_merge_sort::NaturalMergeSort,
+    network_sort::{Network, NetworkSort},
+    options::Options,
+    quick_sort::QuickSort,
+    radix_sort::{LsdRadixSort, MsdRadixSort},
+    selection_sort::SelectionSort,
+    shell_sort::ShellSort,
+    stats::Stats,
+    tim_sort::TimSort,
+    top_down_merge_sort::TopDownMergeSort,
+};
+use strum_macros::{EnumIter, EnumString};
+
+/// Every algorithm available, so one can be picked from a list or by its name.
+/// Names are parsed ignoring case, e.g. `"quick"` is `Algorithms::Quick`.
+#[derive(PartialEq, Eq, Debug, EnumIter, EnumString, Clone, Copy)]
+#[strum(ascii_case_insensitive)]
+pub enum Algorithms {
+    /// [`BubbleSort`]
+    Bubble,
+    /// [`SelectionSort`]
+    Selection,
+    /// [`InsertionSort`]
+    Insertion,
+    /// [`MergeSort`]
+    Merge,
+    /// [`BogoSort`]
+    Bogo,
+    /// [`QuickSort`]
+    Quick,
+    /// [`HeapSort`]
+    Heap,
+    /// [`ShellSort`]
+    Shell,
+    /// [`LsdRadixSort`]
+    LsdRadix,
+    /// [`MsdRadixSort`]
+    MsdRadix,
+    /// [`CountingSort`]
+    Counting,
+    /// [`BucketSort`]
+    Bucket,
+    /// [`TimSort`]
+    Tim,
+    /// [`IntroSort`]
+    Intro,
+    /// [`IntroSort::pattern_defeating`]
+    Pdq,
+    /// [`TopDownMergeSort`]
+    TopDownMerge,
+    /// [`NaturalMergeSort`]
+    NaturalMerge,
+    /// [`InPlaceMergeSort`]
+    InPlaceMerge,
+    /// [`NetworkSort::bitonic`]
+    Bitonic,
+    /// [`NetworkSort::odd_even_merge`]
+    OddEvenMerge,
+}
+
+impl Algorithms {
+    /// Creates the Sorter of the algorithm, in its initial state.
+    pub fn sorter(&self) -> Box<dyn Sorter> {
+        self.sorter_with(&Options::default())
+    }
+
+    /// Creates the Sorter of the algorithm, in its initial state, tuned by `options`.
+    pub fn sorter_with(&self, options: &Options) -> Box<dyn Sorter> {
+        match self {
+            Algorithms::Bubble => Box::new(BubbleSort::new()),
+            Algorithms::Selection => Box::new(SelectionSort::new()),
+            Algorithms::Insertion => Box::new(InsertionSort::new()),
+            Algorithms::Merge => Box::new(MergeSort::new()),
+            Algorithms::Bogo => Box::new(BogoSort::new()),
+            Algorithms::Quick => Box::new(QuickSort::with_scheme(options.partition, options.pivot)),
+            Algorithms::Heap => Box::new(HeapSort::new()),
+            Algorithms::Shell => Box::new(ShellSort::with_gaps(options.gaps)),
+            Algorithms::LsdRadix => Box::new(LsdRadixSort::with_base(options.base)),
+            Algorithms::MsdRadix => Box::new(MsdRadixSort::with_base(options.base)),
+            Algorithms::Counting => Box::new(CountingSort::new()),
+            Algorithms::Bucket => Box::new(BucketSort::new()),
+            Algorithms::Tim => Box::new(TimSort::new()),
+            Algorithms::Intro => Box::new(IntroSort::new()),
+            Algorithms::Pdq => Box::new(IntroSort::pattern_defeating()),
+            Algorithms::TopDownMerge => Box::new(TopDownMergeSort::new()),
+            Algorithms::NaturalMerge => Box::new(NaturalMergeSort::new()),
+            Algorithms::InPlaceMerge => Box::new(InPlaceMergeSort::new()),
+            Algorithms::Bitonic => Box::new(NetworkSort::bitonic()),
+            Algorithms::OddEvenMerge => Box::new(NetworkSort::odd_even_merge()),
+        }
+    }
+}
+
 /// A Sorter is a sorting algorithm split in two stages: the `step` and the `state`.
 /// A `step` can be any single step an algorithm takes, such as comparing or switching numbers
 /// A `state` controls the variables that the `step` is going to use.
-pub trait Sorter {
+///
+/// Stepping must be deterministic: a clone of a Sorter stepped over a clone of the array
+/// takes exactly the same steps, which is what allows rewinding a run.
+pub trait Sorter: SorterClone {
+    /// Creates the Sorter in its initial state.
     fn new() -> Self
     // The Compiler will complain if we don't do this
     where
         Self: Sized;
 
-    /// Returns the indexes currently being compared or about to switch.
-    fn special(&self) -> (usize, usize);
-
-    /// Returns the reason the special indexes are special.
-    fn reason(&self) -> Reasons;
+    /// Returns the operations performed during the last step, in the order they happened.
+    fn operations(&self) -> &[Operation];
 
     /// Loops all states and reset state.
-    fn run(&mut self, array: &mut Vec<usize>) {
+    /// Returns the operations counted along the way.
+    fn run(&mut self, array: &mut Vec<usize>) -> Stats {
+        let mut stats = Stats::default();
         loop {
-            if self.step(array) {
+            let done = self.step(array);
+            stats.track(self);
+            if done {
                 break;
             }
         }
         self.reset_state();
+        stats
     }
 
     /// Takes a single step in running the algorithm.
@@ -35,6 +176,8 @@ pub trait Sorter {
     fn step(&mut self, array: &mut Vec<usize>) -> bool;
 
     /// Modifying the state is analogous to stepping in a loop.
+    /// Algorithms with many states move through the ones that don't touch the array here,
+    /// stopping at the first one that does.
     /// Returns true if all states have been traversed.
     fn modify_state(&mut self, array: &[usize]) -> bool;
 
@@ -43,12 +186,180 @@ pub trait Sorter {
 
     /// Set the Sorter's state to it's initial state.
     fn reset_state(&mut self);
+
+    /// Seeds the random number generator of randomized algorithms,
+    /// so that runs with the same seed over the same numbers take the same steps.
+    /// The seed is kept when the state is reset. Deterministic algorithms ignore it.
+    fn seed(&mut self, _seed: u64) {}
+
+    /// Describes what the algorithm is up to, beyond the operations of the last step,
+    /// such as the gap ShellSort is currently using. Most algorithms have nothing to add.
+    fn status(&self) -> Option<String> {
+        None
+    }
+
+    /// Returns the auxiliary buffers the algorithm keeps numbers in, such as the buckets of
+    /// RadixSort, so they can be shown along with the array. Most algorithms have none.
+    fn buffers(&self) -> Vec<Buffer> {
+        vec![]
+    }
+
+    /// Returns the ranges of the array the algorithm keeps track of, such as the runs
+    /// TimSort has found or the partitions QuickSort has yet to split,
+    /// as their first and last positions. Most algorithms have none.
+    fn ranges(&self) -> Vec<(usize, usize)> {
+        vec![]
+    }
+
+    /// Returns the range of the array the algorithm is working on, such as the partition
+    /// QuickSort is splitting, as its first and last positions.
+    fn active_range(&self) -> Option<(usize, usize)> {
+        None
+    }
+
+    /// Returns the part of the array the algorithm keeps as a binary heap, if any,
+    /// such as the numbers HeapSort has yet to take out of it.
+    fn heap(&self) -> Option<Heap> {
+        None
+    }
+
+    /// Returns how many numbers the algorithm holds outside of the array at the moment.
+    /// It's checked after every step, so algorithms with buffers count their numbers directly
+    /// rather than building the buffers. Most algorithms hold none.
+    fn memory(&self) -> usize {
+        0
+    }
+
+    /// Returns the sorting network the algorithm applies, if any,
+    /// along with how many of its layers have been applied so far.
+    fn network(&self) -> Option<(&Network, usize)> {
+        None
+    }
+
+    /// Returns the positions of the array the algorithm points at, with their names,
+    /// such as the heads of the halves MergeSort is merging. Most algorithms name none.
+    fn cursors(&self) -> Vec<(String, usize)> {
+        vec![]
+    }
+}
+
+/// Numbers a Sorter keeps outside of the array while sorting.
+#[derive(PartialEq, Eq, Debug, Default, Clone)]
+pub struct Buffer {
+    /// What the buffer holds, such as the digit of a bucket.
+    pub label: String,
+    /// The numbers in the buffer, in order.
+    pub numbers: Vec<usize>,
+    /// The positions of the buffer touched by the last step.
+    pub highlighted: Vec<usize>,
+    /// Positions of the buffer the algorithm points at, with their names.
+    pub cursors: Vec<(String, usize)>,
+}
+
+/// A part of the array kept as a binary max-heap, where the children of the number
+/// `i` positions after the start are `2i + 1` and `2i + 2` positions after it.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Heap {
+    /// Where the heap starts, which is its root.
+    pub start: usize,
+    /// Where the heap ends, inclusive.
+    pub end: usize,
+    /// The number being sifted down, if any.
+    pub sifting: Option<usize>,
+}
+
+impl Heap {
+    /// Returns the children of the number at `i` that are within the heap.
+    pub fn children(&self, i: usize) -> Vec<usize> {
+        let first = self.start + (i - self.start) * 2 + 1;
+        (first..=first + 1).filter(|&c| c <= self.end).collect()
+    }
+}
+
+/// Allows cloning boxed Sorters, so their state can be saved and restored.
+/// It's implemented for every Sorter that derives `Clone`.
+pub trait SorterClone {
+    /// Returns a boxed copy of the Sorter, state included.
+    fn clone_box(&self) -> Box<dyn Sorter>;
+}
+
+impl<T: Sorter + Clone + 'static> SorterClone for T {
+    fn clone_box(&self) -> Box<dyn Sorter> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn Sorter> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// A single operation performed by a Sorter while taking a step.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Operation {
+    /// The numbers at both indexes have been compared.
+    Compare(usize, usize),
+    /// The numbers at both indexes have been switched.
+    Swap(usize, usize),
+    /// The number at the index has been read, e.g. to be copied somewhere else.
+    Read(usize),
+    /// The value (second field) has been written to the index (first field).
+    Write(usize, usize),
+    /// The value (second field) has been written to an auxiliary buffer at the index (first field).
+    /// The index is the slot of the buffer as given by [`Sorter::buffers`], so it's the position
+    /// in the array for buffers lined up with it, however much of the array they hold.
+    AuxWrite(usize, usize),
+    /// The number at the index has been chosen as a pivot.
+    MarkPivot(usize),
+    /// The index is a boundary between the sides of a partition being built.
+    MarkBoundary(usize),
+    /// The algorithm started working on the range between both indexes, inclusive.
+    EnterRange(usize, usize),
 }
 
-#[derive(PartialEq, Clone, Copy)]
+impl Operation {
+    /// Returns the indexes of the array that should be highlighted for this operation.
+    pub fn indexes(&self) -> Vec<usize> {
+        match *self {
+            Operation::Compare(a, b) | Operation::Swap(a, b) => vec![a, b],
+            Operation::Read(i)
+            | Operation::Write(i, _)
+            | Operation::MarkPivot(i)
+            | Operation::MarkBoundary(i) => vec![i],
+            Operation::AuxWrite(..) | Operation::EnterRange(..) => vec![],
+        }
+    }
+
+    /// Returns the reason the indexes of this operation are highlighted, if they are.
+    pub fn reason(&self) -> Option<Reasons> {
+        match self {
+            Operation::Compare(..) => Some(Reasons::Comparing),
+            Operation::Swap(..) => Some(Reasons::Switching),
+            Operation::Read(..) => Some(Reasons::Reading),
+            Operation::Write(..) => Some(Reasons::Writing),
+            Operation::MarkPivot(..) => Some(Reasons::Pivot),
+            Operation::MarkBoundary(..) => Some(Reasons::Boundary),
+            Operation::AuxWrite(..) | Operation::EnterRange(..) => None,
+        }
+    }
+}
+
+/// Why numbers are highlighted, which is the kind of operation that touched them.
+#[derive(PartialEq, Debug, Clone, Copy)]
 pub enum Reasons {
+    /// The numbers have been compared.
     Comparing,
+    /// The numbers have been switched.
     Switching,
+    /// The number has been read.
+    Reading,
+    /// The number has been written.
+    Writing,
+    /// The number is a pivot.
+    Pivot,
+    /// The number is at a boundary of a partition.
+    Boundary,
 }
 
 // contants used to test run()
@@ -60,3 +371,175 @@ const FLOOR: usize = 0;
 const CEIL: usize = 100;
 #[cfg(test)]
 const SIZE: usize = 30;
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        bogo_sort::BogoSort,
+        bubble_sort::BubbleSort,
+        bucket_sort::BucketSort,
+        counting_sort::CountingSort,
+        heap_sort::HeapSort,
+        in_place_merge_sort::InPlaceMergeSort,
+        insertion_sort::InsertionSort,
+        intro_sort::IntroSort,
+        merge_sort::MergeSort,
+        natural_merge_sort::NaturalMergeSort,
+        network_sort::NetworkSort,
+        quick_sort::QuickSort,
+        radix_sort::{LsdRadixSort, MsdRadixSort},
+        selection_sort::SelectionSort,
+        shell_sort::ShellSort,
+        tim_sort::TimSort,
+        top_down_merge_sort::TopDownMergeSort,
+        Operation, Sorter, CEIL, FLOOR, REPETITIONS, SIZE,
+    };
+    use crate::util;
+    use rand::thread_rng;
+
+    /// Steps through `sorter` applying its swaps and writes to a copy of the array.
+    /// The copy must always match the array, so no modification goes unreported.
+    fn replay(mut sorter: impl Sorter, size: usize) {
+        let mut array = util::gen_random_vector(FLOOR, CEIL, size, &mut thread_rng());
+        let mut copy = array.clone();
+        loop {
+            let done = sorter.step(&mut array);
+            for operation in sorter.operations() {
+                match *operation {
+                    Operation::Swap(a, b) => copy.swap(a, b),
+                    Operation::Write(i, value) => copy[i] = value,
+                    _ => {}
+                }
+            }
+            assert_eq!(array, copy);
+            if done {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn operations_replay() {
+        replay(BubbleSort::new(), SIZE);
+        replay(SelectionSort::new(), SIZE);
+        replay(InsertionSort::new(), SIZE);
+        replay(MergeSort::new(), SIZE);
+        replay(QuickSort::new(), SIZE);
+        replay(HeapSort::new(), SIZE);
+        replay(ShellSort::new(), SIZE);
+        replay(LsdRadixSort::new(), SIZE);
+        replay(MsdRadixSort::new(), SIZE);
+        replay(CountingSort::new(), SIZE);
+        replay(BucketSort::new(), SIZE);
+        replay(TimSort::new(), SIZE);
+        replay(IntroSort::new(), SIZE);
+        replay(IntroSort::pattern_defeating(), SIZE);
+        replay(TopDownMergeSort::new(), SIZE);
+        replay(NaturalMergeSort::new(), SIZE);
+        replay(InPlaceMergeSort::new(), SIZE);
+        replay(NetworkSort::bitonic(), SIZE);
+        replay(NetworkSort::odd_even_merge(), SIZE);
+        replay(BogoSort::new(), 4);
+    }
+
+    /// Steps through `sorter`, checking that its cursors and those of its buffers
+    /// always point within the array and the buffers.
+    fn cursors_in_bounds(mut sorter: impl Sorter, size: usize) {
+        let mut array = util::gen_random_vector(FLOOR, CEIL, size, &mut thread_rng());
+        loop {
+            let done = sorter.step(&mut array);
+            for (name, position) in sorter.cursors() {
+                assert!(position < array.len(), "{name} at {position}");
+            }
+            for buffer in sorter.buffers() {
+                for (name, position) in buffer.cursors {
+                    assert!(position < buffer.numbers.len(), "{name} at {position}");
+                }
+            }
+            if done {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn cursors() {
+        for _ in 0..REPETITIONS {
+            cursors_in_bounds(MergeSort::new(), SIZE);
+            cursors_in_bounds(TimSort::new(), SIZE);
+            cursors_in_bounds(TopDownMergeSort::new(), SIZE);
+            cursors_in_bounds(NaturalMergeSort::new(), SIZE);
+            cursors_in_bounds(InPlaceMergeSort::new(), SIZE);
+        }
+    }
+
+    /// Steps through `sorter`, checking that each number written to a buffer
+    /// is in one of its buffers at the index written to.
+    fn aux_writes_in_buffers(mut sorter: impl Sorter, size: usize) {
+        let mut array = util::gen_random_vector(FLOOR, CEIL, size, &mut thread_rng());
+        while !sorter.step(&mut array) {
+            let buffers = sorter.buffers();
+            for operation in sorter.operations() {
+                if let &Operation::AuxWrite(index, value) = operation {
+                    let found = buffers
+                        .iter()
+                        .any(|buffer| buffer.numbers.get(index) == Some(&value));
+                    assert!(found, "{value} isn't at {index}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn aux_writes() {
+        for _ in 0..REPETITIONS {
+            aux_writes_in_buffers(MergeSort::new(), SIZE);
+            aux_writes_in_buffers(TimSort::new(), SIZE);
+            aux_writes_in_buffers(TopDownMergeSort::new(), SIZE);
+            aux_writes_in_buffers(NaturalMergeSort::new(), SIZE);
+            aux_writes_in_buffers(CountingSort::new(), SIZE);
+            aux_writes_in_buffers(LsdRadixSort::new(), SIZE);
+            aux_writes_in_buffers(MsdRadixSort::new(), SIZE);
+            aux_writes_in_buffers(BucketSort::new(), SIZE);
+        }
+    }
+
+    /// Sorts every array of up to `max_size` numbers between 0 and 2.
+    fn sort_small(mut sorter: impl Sorter, max_size: u32) {
+        for size in 0..=max_size {
+            for code in 0..3_usize.pow(size) {
+                let mut array: Vec<usize> = (0..size).map(|i| code / 3_usize.pow(i) % 3).collect();
+                let mut expected = array.clone();
+                expected.sort();
+                sorter.run(&mut array);
+                assert_eq!(array, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn small_arrays() {
+        sort_small(BubbleSort::new(), 6);
+        sort_small(SelectionSort::new(), 6);
+        sort_small(InsertionSort::new(), 6);
+        sort_small(MergeSort::new(), 6);
+        sort_small(QuickSort::new(), 6);
+        sort_small(HeapSort::new(), 6);
+        sort_small(ShellSort::new(), 6);
+        sort_small(LsdRadixSort::with_base(2), 6);
+        sort_small(MsdRadixSort::with_base(2), 6);
+        sort_small(CountingSort::new(), 6);
+        sort_small(BucketSort::new(), 6);
+        // Long enough to be split into runs that are merged
+        sort_small(TimSort::new(), 9);
+        sort_small(IntroSort::new(), 8);
+        sort_small(IntroSort::pattern_defeating(), 8);
+        sort_small(TopDownMergeSort::new(), 6);
+        sort_small(NaturalMergeSort::new(), 6);
+        sort_small(InPlaceMergeSort::new(), 6);
+        // Sizes that aren't powers of two leave parts of the networks out
+        sort_small(NetworkSort::bitonic(), 7);
+        sort_small(NetworkSort::odd_even_merge(), 7);
+        sort_small(BogoSort::new(), 4);
+    }
+}
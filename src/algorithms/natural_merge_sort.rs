@@ -0,0 +1,224 @@
+use std::mem;
+
+use super::{merge_sort::Merge, Buffer, Operation, Sorter};
+
+/// Finds the ascending runs the array already has, then merges neighbouring runs
+/// pass after pass until a single one is left, using a buffer as long as the runs being merged.
+#[derive(Clone)]
+pub struct NaturalMergeSort {
+    /// Where the run being scanned starts.
+    start: usize,
+    /// The next position to be compared with the one before it, until the array is scanned.
+    scanned: usize,
+    /// The runs of this pass, the ones before `next` already merged or being merged.
+    runs: Vec<(usize, usize)>,
+    next: usize,
+    /// The runs this pass has merged, which the next pass will merge in turn.
+    merged: Vec<(usize, usize)>,
+    merge: Option<Merge>,
+    operations: Vec<Operation>,
+    /// How many numbers are being sorted, known from the first step.
+    len: usize,
+}
+
+impl NaturalMergeSort {
+    /// Compares the number at `scanned` with the one before it,
+    /// ending the run being scanned if it's lower.
+    fn scan(&mut self, array: &[usize]) {
+        let i = self.scanned;
+        self.operations.push(Operation::Compare(i - 1, i));
+        if array[i - 1] > array[i] {
+            self.runs.push((self.start, i - 1));
+            self.operations.push(Operation::MarkBoundary(i));
+            self.start = i;
+        }
+        self.scanned += 1;
+        if self.scanned == array.len() {
+            self.runs.push((self.start, array.len() - 1));
+        }
+    }
+}
+
+impl Sorter for NaturalMergeSort {
+    fn new() -> Self {
+        NaturalMergeSort {
+            start: 0,
+            scanned: 1,
+            runs: vec![],
+            next: 0,
+            merged: vec![],
+            merge: None,
+            operations: vec![],
+            len: 0,
+        }
+    }
+
+    fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    fn step(&mut self, array: &mut Vec<usize>) -> bool {
+        self.operations.clear();
+        self.len = array.len();
+        if self.len < 2 {
+            return true;
+        }
+        if self.modify_state(array) {
+            return true;
+        }
+        self.switch(array);
+        false
+    }
+
+    fn modify_state(&mut self, array: &[usize]) -> bool {
+        if self.scanned < array.len() {
+            return false;
+        }
+        while self.merge.is_none() {
+            if self.next + 1 < self.runs.len() {
+                let (lo, _) = self.runs[self.next];
+                let (mid, hi) = self.runs[self.next + 1];
+                self.merge = Some(Merge::new(lo, mid, hi));
+                self.next += 2;
+            } else if self.next < self.runs.len() {
+                // A run left without a pair waits for the next pass as it is
+                self.merged.push(self.runs[self.next]);
+                self.next += 1;
+            } else if self.merged.len() > 1 {
+                self.runs = mem::take(&mut self.merged);
+                self.next = 0;
+            } else {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn switch(&mut self, array: &mut Vec<usize>) {
+        if self.scanned < array.len() {
+            self.scan(array);
+        } else if let Some(merge) = &mut self.merge {
+            if merge.step(array, &mut self.operations) {
+                self.merged.push((merge.lo, merge.hi));
+                self.merge = None;
+            }
+        }
+    }
+
+    fn reset_state(&mut self) {
+        *self = NaturalMergeSort::new();
+    }
+
+    fn status(&self) -> Option<String> {
+        if self.len < 2 {
+            return None;
+        }
+        if self.scanned < self.len {
+            return Some(format!("Scanning for runs, {} found", self.runs.len()));
+        }
+        let merging = if self.merge.is_some() { 2 } else { 0 };
+        let left = self.merged.len() + merging + self.runs.len() - self.next;
+        Some(format!("Merging runs, {left} left"))
+    }
+
+    /// The runs merged in this pass, followed by the one being merged and the ones still to be.
+    fn ranges(&self) -> Vec<(usize, usize)> {
+        self.merged
+            .iter()
+            .copied()
+            .chain(self.active_range())
+            .chain(self.runs[self.next.min(self.runs.len())..].iter().copied())
+            .collect()
+    }
+
+    fn active_range(&self) -> Option<(usize, usize)> {
+        if self.len < 2 {
+            return None;
+        }
+        if self.scanned < self.len {
+            return Some((self.start, self.scanned - 1));
+        }
+        self.merge.as_ref().map(|merge| (merge.lo, merge.hi))
+    }
+
+    fn buffers(&self) -> Vec<Buffer> {
+        self.merge
+            .iter()
+            .map(|merge| merge.buffer(self.len, &self.operations))
+            .collect()
+    }
+
+    fn cursors(&self) -> Vec<(String, usize)> {
+        self.merge.as_ref().map(Merge::cursors).unwrap_or_default()
+    }
+
+    fn memory(&self) -> usize {
+        self.merge.as_ref().map_or(0, Merge::memory)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NaturalMergeSort;
+    use crate::{
+        algorithms::{
+            Operation, Sorter, {CEIL, FLOOR, REPETITIONS, SIZE},
+        },
+        util,
+    };
+    use rand::thread_rng;
+
+    #[test]
+    fn run() {
+        for _ in 0..REPETITIONS {
+            let mut sorter = NaturalMergeSort::new();
+            let mut array = util::gen_random_vector(FLOOR, CEIL, SIZE, &mut thread_rng());
+
+            let mut expected = array.clone();
+            expected.sort();
+
+            sorter.run(&mut array);
+
+            assert_eq!(array, expected);
+        }
+    }
+
+    #[test]
+    fn runs() {
+        let mut sorter = NaturalMergeSort::new();
+        let mut array = vec![2, 5, 1, 3, 4, 0];
+        for _ in 0..5 {
+            sorter.step(&mut array);
+        }
+        assert_eq!(
+            sorter.operations(),
+            [Operation::Compare(4, 5), Operation::MarkBoundary(5)]
+        );
+        assert_eq!(sorter.ranges(), [(0, 1), (2, 4), (5, 5)]);
+
+        // The first pass merges the first two runs, leaving the last one as it is
+        sorter.step(&mut array);
+        assert_eq!(sorter.active_range(), Some((0, 4)));
+        assert_eq!(sorter.memory(), 5);
+        while sorter.active_range() == Some((0, 4)) {
+            sorter.step(&mut array);
+        }
+        assert_eq!(array, [1, 2, 3, 4, 5, 0]);
+        assert_eq!(sorter.ranges(), [(0, 4), (5, 5)]);
+        assert_eq!(sorter.status().unwrap(), "Merging runs, 2 left");
+
+        // Which the next pass merges with the one merged
+        sorter.step(&mut array);
+        assert_eq!(sorter.active_range(), Some((0, 5)));
+    }
+
+    #[test]
+    fn sorted() {
+        // A single run has nothing to be merged with
+        let mut sorter = NaturalMergeSort::new();
+        let mut array = vec![1, 2, 3, 4];
+        let stats = sorter.run(&mut array);
+        assert_eq!(stats.comparisons, 3);
+        assert_eq!(stats.aux_memory, 0);
+    }
+}
@@ -0,0 +1,451 @@
+use std::{fmt, str::FromStr};
+
+use super::{Operation, Sorter};
+
+/// The most wires [`Network::counterexample`] checks,
+/// as it runs the network over 2 to the power of the wires inputs.
+pub const MAX_CHECKED_WIRES: usize = 24;
+
+/// Comparators between wires, one wire per position of the array, each comparator putting
+/// the lower of its two numbers on its first wire. The comparators are grouped in layers
+/// whose comparators touch different wires, so each layer could be applied all at once.
+#[derive(PartialEq, Eq, Debug, Default, Clone)]
+pub struct Network {
+    /// How many numbers the network sorts.
+    pub wires: usize,
+    /// The comparators of each layer, as their first and second wires.
+    pub layers: Vec<Vec<(usize, usize)>>,
+}
+
+impl Network {
+    /// Bitonic sort, which merges pairs of sorted halves by comparing each number of the first
+    /// with its mirror in the second, then cleaning up each half the same way.
+    /// Networks for sizes other than powers of two leave out the wires past the end.
+    pub fn bitonic(wires: usize) -> Self {
+        let padded = wires.next_power_of_two();
+        let mut layers = vec![];
+        let mut block = 2;
+        while block <= padded {
+            // The first layer of a merge flips the second half, so every comparator goes up
+            layers.push(comparators(wires, |i| i ^ (block - 1)));
+            let mut distance = block / 4;
+            while distance > 0 {
+                layers.push(comparators(wires, |i| i ^ distance));
+                distance /= 2;
+            }
+            block *= 2;
+        }
+        Self::from_layers(wires, layers)
+    }
+
+    /// Batcher's odd-even merge sort, which merges pairs of sorted halves by merging their
+    /// numbers in even and in odd positions, then comparing the neighbours left out of order.
+    pub fn odd_even_merge(wires: usize) -> Self {
+        let mut layers = vec![];
+        let mut half = 1;
+        while half < wires {
+            let mut distance = half;
+            while distance > 0 {
+                let mut layer = vec![];
+                let first = distance % half;
+                for start in (first..wires.saturating_sub(distance)).step_by(2 * distance) {
+                    for i in start..(start + distance).min(wires - distance) {
+                        // Only numbers of the same pair of halves are compared
+                        if i / (2 * half) == (i + distance) / (2 * half) {
+                            layer.push((i, i + distance));
+                        }
+                    }
+                }
+                layers.push(layer);
+                distance /= 2;
+            }
+            half *= 2;
+        }
+        Self::from_layers(wires, layers)
+    }
+
+    /// Creates a network of the non-empty `layers`.
+    fn from_layers(wires: usize, layers: Vec<Vec<(usize, usize)>>) -> Self {
+        let layers = layers
+            .into_iter()
+            .filter(|layer| !layer.is_empty())
+            .collect();
+        Network { wires, layers }
+    }
+
+    /// Returns how many comparators the network has.
+    pub fn size(&self) -> usize {
+        self.layers.iter().map(Vec::len).sum()
+    }
+
+    /// Adds a comparator between the wires `a` and `b`, in either order, to the first layer
+    /// after every layer that touches either wire, so it's applied after the comparators
+    /// added before it. A comparator from a wire to itself is left out.
+    pub fn add(&mut self, a: usize, b: usize) {
+        let (a, b) = (a.min(b), a.max(b));
+        if a == b {
+            return;
+        }
+        let after = self
+            .layers
+            .iter()
+            .rposition(|layer| {
+                layer
+                    .iter()
+                    .any(|&(c, d)| [c, d].contains(&a) || [c, d].contains(&b))
+            })
+            .map_or(0, |i| i + 1);
+        if after == self.layers.len() {
+            self.layers.push(vec![]);
+        }
+        self.layers[after].push((a, b));
+        self.layers[after].sort();
+    }
+
+    /// Removes the `index`th comparator of the `layer`th layer, and the layer if it's left empty.
+    pub fn remove(&mut self, layer: usize, index: usize) {
+        self.layers[layer].remove(index);
+        if self.layers[layer].is_empty() {
+            self.layers.remove(layer);
+        }
+    }
+
+    /// Sets how many wires the network has, leaving out the comparators past the last one.
+    pub fn resize(&mut self, wires: usize) {
+        let layers = self
+            .layers
+            .iter()
+            .map(|layer| layer.iter().copied().filter(|&(_, b)| b < wires).collect())
+            .collect();
+        *self = Self::from_layers(wires, layers);
+    }
+
+    /// Runs the network over every input of zeros and ones, which by the zero-one principle
+    /// is enough to tell whether it sorts every input.
+    /// Returns an input of zeros and ones the network leaves unsorted, if any.
+    ///
+    /// Networks of more than [`MAX_CHECKED_WIRES`] wires take too long to check,
+    /// so they are refused with an error.
+    pub fn counterexample(&self) -> Result<Option<Vec<usize>>, String> {
+        let wires = self.wires;
+        if wires > MAX_CHECKED_WIRES {
+            return Err(format!(
+                "Networks of more than {MAX_CHECKED_WIRES} wires can't be checked"
+            ));
+        }
+        // Each bit of a mask is the number on a wire
+        let unsorted = (0..1_u64 << wires).find(|&input| {
+            let mut mask = input;
+            for &(a, b) in self.layers.iter().flatten() {
+                if mask >> a & 1 == 1 && mask >> b & 1 == 0 {
+                    mask ^= 1 << a | 1 << b;
+                }
+            }
+            let ones = mask.count_ones() as usize;
+            mask != ((1 << ones) - 1) << (wires - ones)
+        });
+        Ok(unsorted.map(|input| (0..wires).map(|i| (input >> i & 1) as usize).collect()))
+    }
+}
+
+/// Writes how many wires the network has, then one layer per line,
+/// each comparator as its wires joined by a dash, e.g. `0-1 2-3`.
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "wires {}", self.wires)?;
+        for layer in &self.layers {
+            let comparators: Vec<String> = layer.iter().map(|(a, b)| format!("{a}-{b}")).collect();
+            write!(f, "\n{}", comparators.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads a network as written by its [`Display`](fmt::Display), ignoring blank lines
+/// and lines starting with `#`, so networks can be shared with comments on them.
+impl FromStr for Network {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut lines = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+        let wires = lines
+            .next()
+            .and_then(|line| line.strip_prefix("wires"))
+            .and_then(|wires| wires.trim().parse().ok())
+            .ok_or("the first line must be the number of wires, e.g. `wires 4`")?;
+        let mut layers = vec![];
+        for (i, line) in lines.enumerate() {
+            let mut layer: Vec<(usize, usize)> = vec![];
+            for comparator in line.split_whitespace() {
+                let (a, b) = comparator
+                    .split_once('-')
+                    .and_then(|(a, b)| Some((a.parse().ok()?, b.parse().ok()?)))
+                    .ok_or(format!(
+                        "invalid comparator `{comparator}` in layer {}",
+                        i + 1
+                    ))?;
+                if a >= b || b >= wires {
+                    return Err(format!(
+                        "comparator `{comparator}` must go from a wire to a later one, below {wires}"
+                    ));
+                }
+                if layer
+                    .iter()
+                    .any(|&(c, d)| [c, d].contains(&a) || [c, d].contains(&b))
+                {
+                    return Err(format!("layer {} touches a wire twice", i + 1));
+                }
+                layer.push((a, b));
+            }
+            layers.push(layer);
+        }
+        Ok(Self::from_layers(wires, layers))
+    }
+}
+
+/// Returns the comparators between each of the first `wires` wires and its `partner`,
+/// when the partner comes after it and is one of them.
+fn comparators(wires: usize, partner: impl Fn(usize) -> usize) -> Vec<(usize, usize)> {
+    (0..wires)
+        .map(|i| (i, partner(i)))
+        .filter(|&(i, j)| i < j && j < wires)
+        .collect()
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+enum Construction {
+    Bitonic,
+    OddEvenMerge,
+    /// A network given as it is, whatever the size of the array.
+    Custom(Network),
+}
+
+/// Applies a sorting network one layer at a time, every comparator of a layer in the same step.
+/// The network is built for the size of the array on the first step, unless one is given.
+#[derive(Clone)]
+pub struct NetworkSort {
+    construction: Construction,
+    network: Network,
+    /// How many layers have been applied.
+    layer: usize,
+    started: bool,
+    operations: Vec<Operation>,
+}
+
+impl NetworkSort {
+    /// Creates a NetworkSort applying the bitonic sorting network, see [`Network::bitonic`].
+    pub fn bitonic() -> Self {
+        NetworkSort::new()
+    }
+
+    /// Creates a NetworkSort applying Batcher's odd-even merge sorting network,
+    /// see [`Network::odd_even_merge`].
+    pub fn odd_even_merge() -> Self {
+        NetworkSort {
+            construction: Construction::OddEvenMerge,
+            ..NetworkSort::new()
+        }
+    }
+
+    /// Creates a NetworkSort applying `network`, such as one designed by hand.
+    /// Its comparators past the end of the array are skipped.
+    pub fn with_network(network: Network) -> Self {
+        NetworkSort {
+            construction: Construction::Custom(network),
+            ..NetworkSort::new()
+        }
+    }
+}
+
+impl Sorter for NetworkSort {
+    fn new() -> Self {
+        NetworkSort {
+            construction: Construction::Bitonic,
+            network: Network::default(),
+            layer: 0,
+            started: false,
+            operations: vec![],
+        }
+    }
+
+    fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    fn step(&mut self, array: &mut Vec<usize>) -> bool {
+        self.operations.clear();
+        if !self.started {
+            self.started = true;
+            self.network = match &self.construction {
+                Construction::Bitonic => Network::bitonic(array.len()),
+                Construction::OddEvenMerge => Network::odd_even_merge(array.len()),
+                Construction::Custom(network) => network.clone(),
+            };
+        }
+        if self.modify_state(array) {
+            return true;
+        }
+        self.switch(array);
+        false
+    }
+
+    fn modify_state(&mut self, _array: &[usize]) -> bool {
+        self.layer >= self.network.layers.len()
+    }
+
+    fn switch(&mut self, array: &mut Vec<usize>) {
+        let len = array.len();
+        let layer = &self.network.layers[self.layer];
+        for &(a, b) in layer.iter().filter(|&&(_, b)| b < len) {
+            self.operations.push(Operation::Compare(a, b));
+            if array[a] > array[b] {
+                array.swap(a, b);
+                self.operations.push(Operation::Swap(a, b));
+            }
+        }
+        self.layer += 1;
+    }
+
+    fn reset_state(&mut self) {
+        *self = NetworkSort {
+            construction: self.construction.clone(),
+            ..NetworkSort::new()
+        };
+    }
+
+    fn status(&self) -> Option<String> {
+        if self.layer == 0 {
+            return None;
+        }
+        Some(format!(
+            "Applied layer {} of {}, {} comparators in all",
+            self.layer,
+            self.network.layers.len(),
+            self.network.size()
+        ))
+    }
+
+    fn network(&self) -> Option<(&Network, usize)> {
+        self.started.then_some((&self.network, self.layer))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Network, NetworkSort, MAX_CHECKED_WIRES};
+    use crate::{
+        algorithms::{
+            Operation, Sorter, {CEIL, FLOOR, REPETITIONS, SIZE},
+        },
+        util,
+    };
+    use rand::thread_rng;
+    use std::str::FromStr;
+
+    #[test]
+    fn run() {
+        for sorter in [NetworkSort::bitonic(), NetworkSort::odd_even_merge()] {
+            for _ in 0..REPETITIONS {
+                let mut sorter = sorter.clone();
+                let mut array = util::gen_random_vector(FLOOR, CEIL, SIZE, &mut thread_rng());
+
+                let mut expected = array.clone();
+                expected.sort();
+
+                sorter.run(&mut array);
+
+                assert_eq!(array, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn networks() {
+        // The well known sizes of the networks for 8 numbers
+        let bitonic = Network::bitonic(8);
+        assert_eq!((bitonic.layers.len(), bitonic.size()), (6, 24));
+        let odd_even = Network::odd_even_merge(8);
+        assert_eq!((odd_even.layers.len(), odd_even.size()), (6, 19));
+        assert_eq!(odd_even.layers[0], [(0, 1), (2, 3), (4, 5), (6, 7)]);
+        assert_eq!(bitonic.layers[1], [(0, 3), (1, 2), (4, 7), (5, 6)]);
+
+        // No wire is touched twice in a layer
+        for network in [Network::bitonic(13), Network::odd_even_merge(13)] {
+            for layer in &network.layers {
+                let mut wires: Vec<usize> = layer.iter().flat_map(|&(a, b)| [a, b]).collect();
+                wires.sort();
+                wires.dedup();
+                assert_eq!(wires.len(), 2 * layer.len());
+                assert!(layer.iter().all(|&(a, b)| a < b && b < 13));
+            }
+        }
+    }
+
+    #[test]
+    fn layers() {
+        let mut sorter = NetworkSort::odd_even_merge();
+        let mut array = vec![2, 1, 3, 4];
+        assert_eq!(sorter.network(), None);
+        sorter.step(&mut array);
+        // The whole first layer is applied at once
+        assert_eq!(
+            sorter.operations(),
+            [
+                Operation::Compare(0, 1),
+                Operation::Swap(0, 1),
+                Operation::Compare(2, 3)
+            ]
+        );
+        let (network, layer) = sorter.network().unwrap();
+        assert_eq!((network.layers.len(), layer), (3, 1));
+    }
+
+    #[test]
+    fn design() {
+        let mut network = Network {
+            wires: 4,
+            ..Default::default()
+        };
+        network.add(1, 0);
+        network.add(2, 3);
+        network.add(0, 0);
+        // Touching a wire of the first layer, it goes after it
+        network.add(1, 3);
+        network.add(0, 2);
+        assert_eq!(network.layers, [vec![(0, 1), (2, 3)], vec![(0, 2), (1, 3)]]);
+        assert_eq!(network.counterexample(), Ok(Some(vec![1, 0, 1, 0])));
+
+        network.add(1, 2);
+        assert_eq!(network.counterexample(), Ok(None));
+        let mut array = vec![4, 2, 3, 1];
+        NetworkSort::with_network(network.clone()).run(&mut array);
+        assert_eq!(array, [1, 2, 3, 4]);
+
+        network.remove(2, 0);
+        network.resize(3);
+        assert_eq!(network.layers, [vec![(0, 1)], vec![(0, 2)]]);
+
+        // Too many wires to be checked in time, however few comparators
+        network.resize(MAX_CHECKED_WIRES + 1);
+        assert!(network.counterexample().is_err());
+    }
+
+    #[test]
+    fn text() {
+        let network = Network::odd_even_merge(4);
+        let text = network.to_string();
+        assert_eq!(text, "wires 4\n0-1 2-3\n0-2 1-3\n1-2");
+        assert_eq!(Network::from_str(&text), Ok(network));
+
+        let commented = "# Sorts two numbers\n\nwires 2\n  0-1 \n";
+        assert_eq!(Network::from_str(commented).unwrap().layers, [[(0, 1)]]);
+        assert!(Network::from_str("0-1").is_err());
+        assert!(Network::from_str("wires 2\n1-0").is_err());
+        assert!(Network::from_str("wires 2\n0-2").is_err());
+        assert!(Network::from_str("wires 3\n0-1 1-2").is_err());
+        assert!(Network::from_str("wires 3\n0:1").is_err());
+    }
+}
@@ -0,0 +1,30 @@
+use super::{
+    quick_sort::{PartitionScheme, PivotStrategy},
+    radix_sort::DEFAULT_BASE,
+    shell_sort::GapSequence,
+};
+
+/// Settings of the algorithms that can be tuned.
+/// Each algorithm only looks at the settings that concern it.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Options {
+    /// The gaps [`ShellSort`](super::shell_sort::ShellSort) goes through.
+    pub gaps: GapSequence,
+    /// The base the radix sorts read the digits of the numbers in.
+    pub base: usize,
+    /// How [`QuickSort`](super::quick_sort::QuickSort) splits partitions.
+    pub partition: PartitionScheme,
+    /// How [`QuickSort`](super::quick_sort::QuickSort) picks its pivots.
+    pub pivot: PivotStrategy,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            gaps: GapSequence::default(),
+            base: DEFAULT_BASE,
+            partition: PartitionScheme::default(),
+            pivot: PivotStrategy::default(),
+        }
+    }
+}
@@ -1,202 +1,577 @@
-use super::{Reasons, Sorter};
-use crate::ui::constants::VECTOR_SIZE;
+use super::{Operation, Sorter};
+use rand::rngs::StdRng;
+use rand::{thread_rng, Rng, SeedableRng};
+use strum_macros::{EnumIter, EnumString};
 
-fn median(x: usize, y: usize, z: usize, array: &[usize]) -> usize {
+/// Returns the index of the median between the numbers at `x`, `y` and `z`.
+/// The comparisons made are recorded in `operations`.
+pub(super) fn median(
+    x: usize,
+    y: usize,
+    z: usize,
+    array: &[usize],
+    operations: &mut Vec<Operation>,
+) -> usize {
+    operations.extend([Operation::Compare(x, y), Operation::Compare(x, z)]);
     if (array[x] > array[y]) ^ (array[x] > array[z]) {
         return x;
     }
 
+    operations.push(Operation::Compare(y, z));
     if (array[y] > array[x]) ^ (array[y] > array[z]) {
         return y;
     }
     z
 }
 
+/// Returns the index of the median of the medians of three numbers
+/// from the start, the middle and the end of the numbers from `lo` to `hi`.
+/// The comparisons made are recorded in `operations`.
+pub(super) fn ninther(
+    lo: usize,
+    hi: usize,
+    array: &[usize],
+    operations: &mut Vec<Operation>,
+) -> usize {
+    let (mid, t) = ((lo + hi) / 2, (hi - lo) / 8);
+    let a = median(lo, lo + t, lo + 2 * t, array, operations);
+    let b = median(mid - t, mid, mid + t, array, operations);
+    let c = median(hi - 2 * t, hi - t, hi, array, operations);
+    median(a, b, c, array, operations)
+}
+
+/// How QuickSort splits a partition around its pivots.
+/// Names are parsed ignoring case, e.g. `"lomuto"` is `PartitionScheme::Lomuto`.
+#[derive(PartialEq, Eq, Debug, Default, EnumIter, EnumString, Clone, Copy)]
+#[strum(ascii_case_insensitive)]
+pub enum PartitionScheme {
+    /// A single pointer scans the partition, growing the lower side behind it.
+    Lomuto,
+    /// Two pointers move towards each other, switching the numbers on the wrong sides.
+    /// Both stop at numbers equal to the pivot, which spreads them over both sides.
+    #[default]
+    Hoare,
+    /// Dijkstra's Dutch national flag, keeping the numbers equal to the pivot in the middle.
+    ThreeWay,
+    /// Yaroslavskiy's, splitting the partition in three around two pivots.
+    DualPivot,
+}
+
+/// How QuickSort picks its pivots. With two pivots, each is picked from a half of the partition.
+/// Names are parsed ignoring case, e.g. `"ninther"` is `PivotStrategy::Ninther`.
+#[derive(PartialEq, Eq, Debug, Default, EnumIter, EnumString, Clone, Copy)]
+#[strum(ascii_case_insensitive)]
+pub enum PivotStrategy {
+    /// The first number, which is the lowest of sorted numbers.
+    First,
+    /// The last number, which is the highest of sorted numbers.
+    Last,
+    /// Any number, at random.
+    Random,
+    /// The median of the first, middle and last numbers.
+    #[default]
+    MedianOfThree,
+    /// Tukey's median of three medians of three, spread over the partition.
+    Ninther,
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+enum State {
+    Init,
+    /// Picking the next partition.
+    Next,
+    Pivoting,
+    /// Switching the pivots to where the scheme keeps them.
+    MovingPivots,
+    Lomuto,
+    LomutoSwap,
+    /// Moving the left pointer of Hoare's scheme.
+    HoareLeft,
+    /// Moving the right pointer of Hoare's scheme.
+    HoareRight,
+    HoareSwap,
+    ThreeWay,
+    /// Switching a number lower than the pivot to the left.
+    ThreeWaySwapLow,
+    /// Switching a number higher than the pivot to the right.
+    ThreeWaySwapHigh,
+    Dual,
+    /// Switching a number lower than both pivots to the left.
+    DualSwapLow,
+    /// Looking from the right for a number not higher than both pivots.
+    DualRight,
+    /// Switching a number higher than both pivots to the right.
+    DualSwapHigh,
+    /// Checking whether the number switched from the right is lower than both pivots.
+    DualRecheck,
+    /// Switching the pivots between the sides.
+    Placing,
+    Over,
+}
+
+/// Partitions the numbers around a pivot, then each partition.
+/// Both the [scheme](PartitionScheme) and the [pivot](PivotStrategy) can be picked.
+#[derive(Clone)]
 pub struct QuickSort {
-    x: usize,
-    y: usize,
-    pivot_ptr: usize,
-    needs_switch: bool,
-    reason: Reasons,
-    special: (usize, usize),
+    operations: Vec<Operation>,
+    state: State,
+    scheme: PartitionScheme,
+    strategy: PivotStrategy,
+    // Owning the generator makes clones pick the same random pivots
+    rng: StdRng,
+    seed: u64,
     partition_stack: Vec<(usize, usize)>,
-    moving_left_ptr: bool,
-    moving_pivot: bool,
-    returning_pivot: bool,
-    curr_partition_start: usize,
-    curr_partition_end: usize,
+    /// The partition being split.
+    lo: usize,
+    hi: usize,
+    /// Where the pivots currently are.
+    pivots: Vec<usize>,
+    /// Switches to be made before partitioning or after it, in order.
+    moves: Vec<(usize, usize)>,
+    /// Boundaries of the sides being built, which depend on the scheme:
+    /// Lomuto grows the lower side up to `left` while `curr` scans,
+    /// Hoare moves `left` and `right` (exclusive) towards each other,
+    /// the three way and dual pivot schemes keep the lower side before `left`,
+    /// the higher side after `right` and scan with `curr` between them.
+    left: usize,
+    curr: usize,
+    right: usize,
+    /// The partitions left once the pivots are placed.
+    children: Vec<(usize, usize)>,
 }
 
-impl Sorter for QuickSort {
-    fn new() -> QuickSort {
+impl QuickSort {
+    /// Creates a QuickSort splitting partitions with `scheme` around pivots picked with `strategy`.
+    pub fn with_scheme(scheme: PartitionScheme, strategy: PivotStrategy) -> QuickSort {
         QuickSort {
-            x: 0,
-            y: VECTOR_SIZE - 2,
-            pivot_ptr: usize::MAX,
-            needs_switch: false,
-            reason: Reasons::Comparing,
-            special: (usize::MAX, usize::MAX),
-            partition_stack: vec![(0, VECTOR_SIZE - 1)],
-            moving_left_ptr: true,
-            moving_pivot: true,
-            returning_pivot: false,
-            curr_partition_start: 0,
-            curr_partition_end: VECTOR_SIZE - 1,
+            scheme,
+            strategy,
+            ..QuickSort::new()
         }
     }
 
-    fn special(&self) -> (usize, usize) {
-        if self.pivot_ptr == usize::MAX {
-            return (usize::MAX, usize::MAX);
+    /// Returns the pivot picked between `lo` and `hi`.
+    fn pick(&mut self, lo: usize, hi: usize, array: &[usize]) -> usize {
+        let operations = &mut self.operations;
+        match self.strategy {
+            PivotStrategy::First => lo,
+            PivotStrategy::Last => hi,
+            PivotStrategy::Random => self.rng.gen_range(lo..=hi),
+            // A ninther needs three numbers in each third
+            PivotStrategy::Ninther if hi - lo >= 8 => ninther(lo, hi, array, operations),
+            PivotStrategy::MedianOfThree | PivotStrategy::Ninther => {
+                median(lo, (lo + hi) / 2, hi, array, operations)
+            }
         }
-        self.special
-    }
-
-    fn reason(&self) -> super::Reasons {
-        self.reason
     }
 
-    fn step(&mut self, array: &mut Vec<usize>) -> bool {
-        if self.needs_switch {
-            self.switch(array);
-        } else {
-            return self.modify_state(array);
+    /// Switches the numbers at `a` and `b`, keeping track of the pivots.
+    fn swap(&mut self, array: &mut [usize], a: usize, b: usize) {
+        array.swap(a, b);
+        self.operations.push(Operation::Swap(a, b));
+        for pivot in &mut self.pivots {
+            if *pivot == a {
+                *pivot = b;
+            } else if *pivot == b {
+                *pivot = a;
+            }
         }
-        false
     }
 
-    fn modify_state(&mut self, array: &[usize]) -> bool {
-        if self.partition_stack.is_empty() {
-            return true;
+    /// Queues the numbers from `lo` up to `end` (exclusive) to be partitioned,
+    /// unless they are already sorted for being fewer than 2.
+    fn child(&mut self, lo: usize, end: usize) {
+        if end > lo + 1 {
+            self.children.push((lo, end - 1));
         }
+    }
 
-        self.reason = Reasons::Comparing;
-
-        if self.pivot_ptr == usize::MAX {
-            (self.curr_partition_start, self.curr_partition_end) =
-                *self.partition_stack.last().unwrap();
-            self.pivot_ptr = median(
-                self.curr_partition_start,
-                (self.curr_partition_end - self.curr_partition_start) / 2
-                    + self.curr_partition_start,
-                self.curr_partition_end,
-                array,
-            );
-            self.special = (self.pivot_ptr, self.curr_partition_end);
-            if self.moving_pivot {
-                self.needs_switch = true;
-            }
-            return false;
+    /// Starts placing the pivots at `pivots`, leaving the partitions between them.
+    fn place(&mut self, pivots: &[usize]) {
+        let (lo, hi) = (self.lo, self.hi);
+        self.moves.clear();
+        match pivots {
+            [pivot] => {
+                if *pivot != hi {
+                    self.moves.push((*pivot, hi));
+                }
+                self.child(lo, *pivot);
+                self.child(pivot + 1, hi + 1);
+            }
+            [low, high] => {
+                if *low != lo {
+                    self.moves.push((lo, *low));
+                }
+                if *high != hi {
+                    self.moves.push((hi, *high));
+                }
+                self.child(lo, *low);
+                self.child(low + 1, *high);
+                self.child(high + 1, hi + 1);
+            }
+            _ => {}
         }
+        self.state = State::Placing;
+    }
 
-        if self.moving_pivot {
-            if self.returning_pivot {
-                self.special = (self.pivot_ptr, self.x);
-            } else {
-                self.special = (self.pivot_ptr, self.curr_partition_end);
+    /// Returns the boundaries of the sides being built, within the partition.
+    fn boundaries(&self) -> Vec<usize> {
+        let boundaries = match self.state {
+            State::Lomuto | State::LomutoSwap => vec![self.left, self.curr],
+            State::HoareLeft | State::HoareRight | State::HoareSwap => {
+                vec![self.left, self.right.saturating_sub(1)]
             }
-            self.needs_switch = true;
-            return false;
+            State::ThreeWay
+            | State::ThreeWaySwapLow
+            | State::ThreeWaySwapHigh
+            | State::Dual
+            | State::DualSwapLow
+            | State::DualRight
+            | State::DualSwapHigh
+            | State::DualRecheck => vec![self.left, self.curr, self.right],
+            _ => vec![],
+        };
+        boundaries
+            .into_iter()
+            .filter(|i| (self.lo..=self.hi).contains(i))
+            .collect()
+    }
+}
+
+impl Sorter for QuickSort {
+    fn new() -> QuickSort {
+        let seed = thread_rng().gen();
+        QuickSort {
+            operations: vec![],
+            state: State::Init,
+            scheme: PartitionScheme::default(),
+            strategy: PivotStrategy::default(),
+            rng: StdRng::seed_from_u64(seed),
+            seed,
+            partition_stack: vec![],
+            lo: 0,
+            hi: 0,
+            pivots: vec![],
+            moves: vec![],
+            left: 0,
+            curr: 0,
+            right: 0,
+            children: vec![],
         }
+    }
 
-        self.special = (self.x, self.y);
+    fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
 
-        // Se ponteiros se cruzarem
-        if self.y < self.x {
-            self.moving_pivot = true;
-            self.returning_pivot = true;
-            self.needs_switch = true;
-            return false;
+    fn step(&mut self, array: &mut Vec<usize>) -> bool {
+        self.operations.clear();
+        if self.modify_state(array) {
+            return true;
         }
+        // Boundaries go first, so the numbers compared or switched are highlighted over them
+        let boundaries = self.boundaries();
+        self.operations
+            .extend(boundaries.into_iter().map(Operation::MarkBoundary));
+        self.switch(array);
+        let pivots = self.pivots.iter().copied().map(Operation::MarkPivot);
+        self.operations.extend(pivots);
+        false
+    }
 
-        if self.moving_left_ptr {
-            if array[self.x] < array[self.pivot_ptr] {
-                self.x += 1;
-            } else {
-                self.moving_left_ptr = false;
-            }
-        } else if array[self.y] >= array[self.pivot_ptr] {
-            if self.y == self.curr_partition_start {
-                self.needs_switch = true;
-                self.returning_pivot = true;
-                self.moving_pivot = true;
-            } else {
-                self.y -= 1;
-            }
-        } else {
-            self.moving_left_ptr = true;
-            self.needs_switch = true;
+    fn modify_state(&mut self, array: &[usize]) -> bool {
+        loop {
+            match self.state {
+                State::Init => {
+                    if array.len() >= 2 {
+                        self.partition_stack.push((0, array.len() - 1));
+                    }
+                    self.state = State::Next;
+                }
+                State::Next => {
+                    let Some((lo, hi)) = self.partition_stack.pop() else {
+                        self.state = State::Over;
+                        continue;
+                    };
+                    (self.lo, self.hi) = (lo, hi);
+                    self.operations.push(Operation::EnterRange(lo, hi));
+                    self.state = State::Pivoting;
+                }
+                State::MovingPivots => {
+                    if !self.moves.is_empty() {
+                        return false;
+                    }
+                    let (lo, hi) = (self.lo, self.hi);
+                    (self.left, self.curr, self.right, self.state) = match self.scheme {
+                        PartitionScheme::Lomuto => (lo, lo, hi, State::Lomuto),
+                        PartitionScheme::Hoare => (lo, lo, hi, State::HoareLeft),
+                        PartitionScheme::ThreeWay => (lo, lo + 1, hi, State::ThreeWay),
+                        PartitionScheme::DualPivot => (lo + 1, lo + 1, hi - 1, State::Dual),
+                    };
+                }
+                State::Lomuto => {
+                    if self.curr < self.hi {
+                        return false;
+                    }
+                    self.place(&[self.left]);
+                }
+                State::HoareLeft => {
+                    if self.left < self.right {
+                        return false;
+                    }
+                    self.place(&[self.left]);
+                }
+                State::HoareRight => {
+                    // The number at the left pointer is already known not to be lower
+                    if self.right > self.left + 1 {
+                        return false;
+                    }
+                    self.place(&[self.left]);
+                }
+                State::ThreeWay => {
+                    if self.curr <= self.right {
+                        return false;
+                    }
+                    self.moves.clear();
+                    self.child(self.lo, self.left);
+                    self.child(self.right + 1, self.hi + 1);
+                    self.state = State::Placing;
+                }
+                State::Dual => {
+                    if self.curr <= self.right {
+                        return false;
+                    }
+                    self.place(&[self.left - 1, self.right + 1]);
+                }
+                State::DualRight => {
+                    if self.curr < self.right {
+                        return false;
+                    }
+                    // Nothing is left to the right, so the higher number joins that side
+                    self.right -= 1;
+                    self.state = State::Dual;
+                }
+                State::Placing => {
+                    if !self.moves.is_empty() {
+                        return false;
+                    }
+                    // The leftmost partition goes last, to be split first
+                    while let Some(child) = self.children.pop() {
+                        self.partition_stack.push(child);
+                    }
+                    self.pivots.clear();
+                    self.state = State::Next;
+                }
+                State::Over => return true,
+                State::Pivoting
+                | State::LomutoSwap
+                | State::HoareSwap
+                | State::ThreeWaySwapLow
+                | State::ThreeWaySwapHigh
+                | State::DualSwapLow
+                | State::DualSwapHigh
+                | State::DualRecheck => return false,
+            }
         }
-        false
     }
 
     fn switch(&mut self, array: &mut Vec<usize>) {
-        self.reason = Reasons::Switching;
-        if self.moving_pivot {
-            if self.returning_pivot {
-                self.special = (self.x, self.pivot_ptr);
-                array.swap(self.x, self.pivot_ptr);
-                self.moving_pivot = true;
-                self.returning_pivot = false;
-
-                if self.x - self.curr_partition_start >= 2 {
-                    self.partition_stack
-                        .push((self.curr_partition_start, self.x - 1));
+        let (lo, hi) = (self.lo, self.hi);
+        match self.state {
+            State::Pivoting => {
+                self.moves.clear();
+                if self.scheme == PartitionScheme::DualPivot {
+                    let mid = (lo + hi) / 2;
+                    let mut low = self.pick(lo, mid, array);
+                    let mut high = self.pick(mid + 1, hi, array);
+                    self.operations.push(Operation::Compare(low, high));
+                    if array[low] > array[high] {
+                        (low, high) = (high, low);
+                    }
+                    self.pivots = vec![low, high];
+                    // The lower pivot goes to the start and the higher one to the end
+                    if low != lo {
+                        self.moves.push((low, lo));
+                    }
+                    let high = if high == lo { low } else { high };
+                    if high != hi {
+                        self.moves.push((high, hi));
+                    }
+                } else {
+                    let pivot = self.pick(lo, hi, array);
+                    self.pivots = vec![pivot];
+                    // The three way scheme keeps the pivot at the start of the equal numbers
+                    let target = if self.scheme == PartitionScheme::ThreeWay {
+                        lo
+                    } else {
+                        hi
+                    };
+                    if pivot != target {
+                        self.moves.push((pivot, target));
+                    }
                 }
-
-                if self.curr_partition_end - self.x >= 2 {
-                    self.partition_stack
-                        .push((self.x + 1, self.curr_partition_end));
+                self.state = State::MovingPivots;
+            }
+            State::MovingPivots | State::Placing => {
+                let (a, b) = self.moves.remove(0);
+                self.swap(array, a, b);
+            }
+            State::Lomuto => {
+                self.operations.push(Operation::Compare(self.curr, hi));
+                if array[self.curr] < array[hi] {
+                    if self.left != self.curr {
+                        self.state = State::LomutoSwap;
+                        return;
+                    }
+                    self.left += 1;
                 }
-
-                if !self.partition_stack.is_empty()
-                    && *self.partition_stack.last().unwrap() == (0, VECTOR_SIZE - 1)
-                {
-                    self.partition_stack.pop();
+                self.curr += 1;
+            }
+            State::LomutoSwap => {
+                self.swap(array, self.left, self.curr);
+                self.left += 1;
+                self.curr += 1;
+                self.state = State::Lomuto;
+            }
+            State::HoareLeft => {
+                self.operations.push(Operation::Compare(self.left, hi));
+                if array[self.left] < array[hi] {
+                    self.left += 1;
+                } else {
+                    self.state = State::HoareRight;
                 }
-
-                if !self.partition_stack.is_empty() {
-                    (self.curr_partition_start, self.curr_partition_end) =
-                        self.partition_stack.pop().unwrap();
+            }
+            State::HoareRight => {
+                let candidate = self.right - 1;
+                self.operations.push(Operation::Compare(candidate, hi));
+                if array[candidate] > array[hi] {
+                    self.right -= 1;
+                } else {
+                    self.state = State::HoareSwap;
+                }
+            }
+            State::HoareSwap => {
+                self.swap(array, self.left, self.right - 1);
+                self.left += 1;
+                self.right -= 1;
+                self.state = State::HoareLeft;
+            }
+            State::ThreeWay => {
+                // The first of the numbers equal to the pivot is always at the left boundary
+                self.operations
+                    .push(Operation::Compare(self.curr, self.left));
+                if array[self.curr] < array[self.left] {
+                    self.state = State::ThreeWaySwapLow;
+                } else if array[self.curr] > array[self.left] {
+                    self.state = State::ThreeWaySwapHigh;
+                } else {
+                    self.curr += 1;
                 }
-                (self.x, self.y) = (self.curr_partition_start, self.curr_partition_end);
-                self.pivot_ptr = median(self.x, (self.x + self.y) / 2, self.y, array);
-            } else {
-                if self.partition_stack.is_empty() {
-                    self.needs_switch = false;
-                    return;
+            }
+            State::ThreeWaySwapLow => {
+                self.swap(array, self.left, self.curr);
+                self.left += 1;
+                self.curr += 1;
+                self.state = State::ThreeWay;
+            }
+            State::ThreeWaySwapHigh => {
+                self.swap(array, self.curr, self.right);
+                self.right -= 1;
+                self.state = State::ThreeWay;
+            }
+            State::Dual | State::DualRecheck => {
+                self.operations.push(Operation::Compare(self.curr, lo));
+                if array[self.curr] < array[lo] {
+                    if self.left != self.curr {
+                        self.state = State::DualSwapLow;
+                        return;
+                    }
+                    self.left += 1;
+                } else if self.state == State::Dual {
+                    self.operations.push(Operation::Compare(self.curr, hi));
+                    if array[self.curr] > array[hi] {
+                        self.state = State::DualRight;
+                        return;
+                    }
                 }
-                array.swap(self.pivot_ptr, self.curr_partition_end);
-                self.pivot_ptr = self.curr_partition_end;
-                self.needs_switch = false;
-                self.moving_pivot = false;
+                self.curr += 1;
+                self.state = State::Dual;
+            }
+            State::DualSwapLow => {
+                self.swap(array, self.left, self.curr);
+                self.left += 1;
+                self.curr += 1;
+                self.state = State::Dual;
             }
-            return;
+            State::DualRight => {
+                self.operations.push(Operation::Compare(self.right, hi));
+                if array[self.right] > array[hi] {
+                    self.right -= 1;
+                } else {
+                    self.state = State::DualSwapHigh;
+                }
+            }
+            State::DualSwapHigh => {
+                self.swap(array, self.curr, self.right);
+                self.right -= 1;
+                self.state = State::DualRecheck;
+            }
+            State::Init | State::Next | State::Over => {}
         }
-        array.swap(self.x, self.y);
-        self.needs_switch = false;
     }
 
     fn reset_state(&mut self) {
-        *self = QuickSort::new();
+        let seed = self.seed;
+        *self = QuickSort::with_scheme(self.scheme, self.strategy);
+        self.seed(seed);
+    }
+
+    fn seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    fn status(&self) -> Option<String> {
+        match self.state {
+            State::Init | State::Next | State::Over => None,
+            _ => Some(format!(
+                "Partitioning {}-{} with {:?}, {:?} pivot",
+                self.lo, self.hi, self.scheme, self.strategy
+            )),
+        }
+    }
+
+    fn ranges(&self) -> Vec<(usize, usize)> {
+        let mut ranges = self.partition_stack.clone();
+        ranges.extend(self.active_range());
+        ranges
+    }
+
+    fn active_range(&self) -> Option<(usize, usize)> {
+        match self.state {
+            State::Init | State::Next | State::Over => None,
+            _ => Some((self.lo, self.hi)),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{QuickSort, VECTOR_SIZE};
+    use super::{PartitionScheme, PivotStrategy, QuickSort};
     use crate::{
         algorithms::{
-            Sorter, {CEIL, FLOOR, REPETITIONS},
+            Operation, Sorter, {CEIL, FLOOR, REPETITIONS, SIZE},
         },
         util,
     };
+    use rand::thread_rng;
+    use strum::IntoEnumIterator;
 
     #[test]
     fn run() {
         for _ in 0..REPETITIONS {
             let mut sorter = QuickSort::new();
-            let mut array = util::gen_random_vector(FLOOR, CEIL, VECTOR_SIZE);
+            let mut array = util::gen_random_vector(FLOOR, CEIL, SIZE, &mut thread_rng());
             let mut expected = array.clone();
             expected.sort();
 
@@ -205,4 +580,83 @@ mod tests {
             assert_eq!(array, expected);
         }
     }
+
+    #[test]
+    fn schemes() {
+        for scheme in PartitionScheme::iter() {
+            for strategy in PivotStrategy::iter() {
+                for size in [2, 3, SIZE, 100] {
+                    let mut sorter = QuickSort::with_scheme(scheme, strategy);
+                    // Few distinct numbers, so there are plenty of numbers equal to the pivots
+                    let mut array = util::gen_random_vector(0, 5, size, &mut thread_rng());
+                    let mut expected = array.clone();
+                    expected.sort();
+                    sorter.run(&mut array);
+                    assert_eq!(array, expected, "{scheme:?} {strategy:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn hoare_equal() {
+        // Switching the numbers equal to the pivot splits the partitions in halves
+        let mut sorter = QuickSort::with_scheme(PartitionScheme::Hoare, PivotStrategy::Last);
+        let mut array = vec![7; 128];
+        let stats = sorter.run(&mut array);
+        assert!(stats.comparisons < 128 * 10, "{}", stats.comparisons);
+    }
+
+    #[test]
+    fn highlights() {
+        let mut sorter = QuickSort::with_scheme(PartitionScheme::Lomuto, PivotStrategy::Last);
+        let mut array = vec![1, 3, 0, 2];
+        sorter.step(&mut array);
+        assert_eq!(
+            sorter.operations(),
+            [Operation::EnterRange(0, 3), Operation::MarkPivot(3)]
+        );
+        sorter.step(&mut array);
+        sorter.step(&mut array);
+        assert_eq!(
+            sorter.operations(),
+            [
+                Operation::MarkBoundary(1),
+                Operation::MarkBoundary(1),
+                Operation::Compare(1, 3),
+                Operation::MarkPivot(3),
+            ]
+        );
+
+        // The numbers equal to the pivot are left out of both partitions,
+        // which here leaves a single number on each side, so nothing else is partitioned
+        let mut sorter = QuickSort::with_scheme(PartitionScheme::ThreeWay, PivotStrategy::First);
+        let mut array = vec![2, 2, 1, 2, 3, 2];
+        let mut partitions = 0;
+        while !sorter.step(&mut array) {
+            partitions += sorter
+                .operations()
+                .iter()
+                .filter(|op| matches!(op, Operation::EnterRange(..)))
+                .count();
+        }
+        assert_eq!(partitions, 1);
+        assert_eq!(array, [1, 2, 2, 2, 2, 3]);
+    }
+
+    #[test]
+    fn ranges() {
+        let mut sorter = QuickSort::with_scheme(PartitionScheme::Lomuto, PivotStrategy::Last);
+        let mut array = vec![3, 4, 0, 1, 2];
+        sorter.step(&mut array);
+        assert_eq!(sorter.active_range(), Some((0, 4)));
+        assert_eq!(sorter.ranges(), [(0, 4)]);
+
+        // The pivot ends up in the middle, leaving a partition on each side
+        while sorter.active_range() == Some((0, 4)) {
+            sorter.step(&mut array);
+        }
+        assert_eq!(sorter.active_range(), Some((0, 1)));
+        assert_eq!(sorter.ranges(), [(3, 4), (0, 1)]);
+    }
 }
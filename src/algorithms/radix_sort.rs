@@ -0,0 +1,463 @@
+use std::collections::VecDeque;
+
+use super::{Buffer, Operation, Sorter};
+
+/// Base used when none is chosen.
+pub const DEFAULT_BASE: usize = 10;
+/// Smallest base that makes sense, where every digit is either 0 or 1.
+pub const MIN_BASE: usize = 2;
+/// Largest base offered, so the buckets still fit on the screen.
+pub const MAX_BASE: usize = 16;
+
+#[derive(PartialEq, Clone)]
+enum State {
+    Init,
+    Distributing,
+    Collecting,
+    Over,
+}
+
+/// The buckets the numbers are distributed into, such as one for each digit.
+#[derive(Clone)]
+pub(super) struct Buckets {
+    pub(super) buckets: Vec<VecDeque<usize>>,
+    /// The bucket and position written by the last step, if any.
+    pub(super) written: Option<(usize, usize)>,
+}
+
+impl Buckets {
+    pub(super) fn new(count: usize) -> Self {
+        Buckets {
+            buckets: vec![VecDeque::new(); count],
+            written: None,
+        }
+    }
+
+    /// Puts `value` at the end of the `bucket`, returning its position there.
+    pub(super) fn push(&mut self, bucket: usize, value: usize) -> usize {
+        self.buckets[bucket].push_back(value);
+        let position = self.buckets[bucket].len() - 1;
+        self.written = Some((bucket, position));
+        position
+    }
+
+    /// Returns how many numbers are in the buckets.
+    pub(super) fn memory(&self) -> usize {
+        self.buckets.iter().map(VecDeque::len).sum()
+    }
+
+    /// Takes the first number out of the first bucket that isn't empty, starting from `from`.
+    /// Returns the bucket it was in and the number.
+    pub(super) fn pop(&mut self, from: usize) -> Option<(usize, usize)> {
+        (from..self.buckets.len()).find_map(|bucket| {
+            let value = self.buckets[bucket].pop_front()?;
+            Some((bucket, value))
+        })
+    }
+
+    /// Returns a buffer for each bucket, labeled by `label`.
+    pub(super) fn buffers(&self, label: impl Fn(usize) -> String) -> Vec<Buffer> {
+        self.buckets
+            .iter()
+            .enumerate()
+            .map(|(i, bucket)| Buffer {
+                label: label(i),
+                numbers: bucket.iter().copied().collect(),
+                highlighted: match self.written {
+                    Some((written, position)) if written == i => vec![position],
+                    _ => vec![],
+                },
+                ..Default::default()
+            })
+            .collect()
+    }
+}
+
+/// Returns the digit of `value` worth `place` in `base`.
+fn digit(value: usize, place: usize, base: usize) -> usize {
+    value / place % base
+}
+
+/// Distributes the numbers into buckets by their least significant digit and collects them back,
+/// repeating for every digit up to the most significant one.
+#[derive(Clone)]
+pub struct LsdRadixSort {
+    operations: Vec<Operation>,
+    state: State,
+    base: usize,
+    buckets: Buckets,
+    /// What the digit being sorted by is worth: 1, base, base², ...
+    place: usize,
+    max: usize,
+    /// The position being read from when distributing, or written to when collecting.
+    i: usize,
+    /// The bucket being collected.
+    bucket: usize,
+}
+
+impl LsdRadixSort {
+    /// Creates a LsdRadixSort that reads the numbers as digits in `base`.
+    pub fn with_base(base: usize) -> LsdRadixSort {
+        LsdRadixSort {
+            base: base.max(MIN_BASE),
+            buckets: Buckets::new(base.max(MIN_BASE)),
+            ..LsdRadixSort::new()
+        }
+    }
+}
+
+impl Sorter for LsdRadixSort {
+    fn new() -> LsdRadixSort {
+        LsdRadixSort {
+            operations: vec![],
+            state: State::Init,
+            base: DEFAULT_BASE,
+            buckets: Buckets::new(DEFAULT_BASE),
+            place: 1,
+            max: 0,
+            i: 0,
+            bucket: 0,
+        }
+    }
+
+    fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    fn step(&mut self, array: &mut Vec<usize>) -> bool {
+        self.operations.clear();
+        self.buckets.written = None;
+        if self.modify_state(array) {
+            return true;
+        }
+        self.switch(array);
+        false
+    }
+
+    fn modify_state(&mut self, array: &[usize]) -> bool {
+        if self.state == State::Init {
+            if array.len() < 2 {
+                self.state = State::Over;
+            } else {
+                self.max = array.iter().copied().max().unwrap_or(0);
+                self.state = State::Distributing;
+            }
+        }
+        if self.state == State::Distributing && self.i >= array.len() {
+            self.state = State::Collecting;
+            self.i = 0;
+            self.bucket = 0;
+        }
+        if self.state == State::Collecting && self.i >= array.len() {
+            // Once a digit higher than any number is reached, they are all sorted
+            if self.max / self.place < self.base {
+                self.state = State::Over;
+            } else {
+                self.place *= self.base;
+                self.state = State::Distributing;
+                self.i = 0;
+            }
+        }
+        self.state == State::Over
+    }
+
+    fn switch(&mut self, array: &mut Vec<usize>) {
+        match self.state {
+            State::Distributing => {
+                let value = array[self.i];
+                let digit = digit(value, self.place, self.base);
+                let position = self.buckets.push(digit, value);
+                self.operations.push(Operation::Read(self.i));
+                self.operations.push(Operation::AuxWrite(position, value));
+            }
+            State::Collecting => {
+                let Some((bucket, value)) = self.buckets.pop(self.bucket) else {
+                    return;
+                };
+                self.bucket = bucket;
+                array[self.i] = value;
+                self.operations.push(Operation::Write(self.i, value));
+            }
+            State::Init | State::Over => return,
+        }
+        self.i += 1;
+    }
+
+    fn reset_state(&mut self) {
+        *self = LsdRadixSort::with_base(self.base);
+    }
+
+    fn status(&self) -> Option<String> {
+        match self.state {
+            State::Distributing | State::Collecting => {
+                Some(format!("Digit worth {} in base {}", self.place, self.base))
+            }
+            State::Init | State::Over => None,
+        }
+    }
+
+    fn buffers(&self) -> Vec<Buffer> {
+        self.buckets.buffers(|digit| digit.to_string())
+    }
+
+    fn memory(&self) -> usize {
+        self.buckets.memory()
+    }
+}
+
+/// Distributes the numbers into buckets by their most significant digit and collects them back,
+/// then does the same for each bucket with the next digit.
+#[derive(Clone)]
+pub struct MsdRadixSort {
+    operations: Vec<Operation>,
+    state: State,
+    base: usize,
+    buckets: Buckets,
+    /// Ranges still to be sorted, from `lo` (inclusive) to `hi` (exclusive),
+    /// with what their digit is worth.
+    ranges: Vec<(usize, usize, usize)>,
+    lo: usize,
+    hi: usize,
+    /// What the digit being sorted by is worth: ..., base², base, 1.
+    place: usize,
+    /// How many numbers each bucket got, counted once the range has been distributed.
+    sizes: Vec<usize>,
+    /// The position being read from when distributing, or written to when collecting.
+    i: usize,
+    /// The bucket being collected.
+    bucket: usize,
+}
+
+impl MsdRadixSort {
+    /// Creates a MsdRadixSort that reads the numbers as digits in `base`.
+    pub fn with_base(base: usize) -> MsdRadixSort {
+        MsdRadixSort {
+            base: base.max(MIN_BASE),
+            buckets: Buckets::new(base.max(MIN_BASE)),
+            ..MsdRadixSort::new()
+        }
+    }
+
+    /// Moves on to the next range with more than one number in it.
+    fn next_range(&mut self) {
+        while let Some((lo, hi, place)) = self.ranges.pop() {
+            if hi - lo > 1 {
+                self.lo = lo;
+                self.hi = hi;
+                self.place = place;
+                self.i = lo;
+                self.state = State::Distributing;
+                self.operations.push(Operation::EnterRange(lo, hi - 1));
+                return;
+            }
+        }
+        self.state = State::Over;
+    }
+}
+
+impl Sorter for MsdRadixSort {
+    fn new() -> MsdRadixSort {
+        MsdRadixSort {
+            operations: vec![],
+            state: State::Init,
+            base: DEFAULT_BASE,
+            buckets: Buckets::new(DEFAULT_BASE),
+            ranges: vec![],
+            lo: 0,
+            hi: 0,
+            place: 1,
+            sizes: vec![],
+            i: 0,
+            bucket: 0,
+        }
+    }
+
+    fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    fn step(&mut self, array: &mut Vec<usize>) -> bool {
+        self.operations.clear();
+        self.buckets.written = None;
+        if self.modify_state(array) {
+            return true;
+        }
+        self.switch(array);
+        false
+    }
+
+    fn modify_state(&mut self, array: &[usize]) -> bool {
+        if self.state == State::Init {
+            let max = array.iter().copied().max().unwrap_or(0);
+            let mut place = 1;
+            while max / place >= self.base {
+                place *= self.base;
+            }
+            self.ranges.push((0, array.len(), place));
+            self.next_range();
+        }
+        if self.state == State::Distributing && self.i >= self.hi {
+            self.sizes = self.buckets.buckets.iter().map(VecDeque::len).collect();
+            self.state = State::Collecting;
+            self.i = self.lo;
+            self.bucket = 0;
+        }
+        if self.state == State::Collecting && self.i >= self.hi {
+            // Each bucket is a range of its own, sorted by the next digit
+            if self.place > 1 {
+                let mut start = self.lo;
+                let mut ranges = vec![];
+                for size in &self.sizes {
+                    ranges.push((start, start + size, self.place / self.base));
+                    start += size;
+                }
+                // Reversed, so the lowest bucket is sorted first
+                self.ranges.extend(ranges.into_iter().rev());
+            }
+            self.next_range();
+        }
+        self.state == State::Over
+    }
+
+    fn switch(&mut self, array: &mut Vec<usize>) {
+        match self.state {
+            State::Distributing => {
+                let value = array[self.i];
+                let digit = digit(value, self.place, self.base);
+                let position = self.buckets.push(digit, value);
+                self.operations.push(Operation::Read(self.i));
+                self.operations.push(Operation::AuxWrite(position, value));
+            }
+            State::Collecting => {
+                let Some((bucket, value)) = self.buckets.pop(self.bucket) else {
+                    return;
+                };
+                self.bucket = bucket;
+                array[self.i] = value;
+                self.operations.push(Operation::Write(self.i, value));
+            }
+            State::Init | State::Over => return,
+        }
+        self.i += 1;
+    }
+
+    fn reset_state(&mut self) {
+        *self = MsdRadixSort::with_base(self.base);
+    }
+
+    fn status(&self) -> Option<String> {
+        match self.state {
+            State::Distributing | State::Collecting => Some(format!(
+                "Digit worth {} in base {}, from {} to {}",
+                self.place,
+                self.base,
+                self.lo,
+                self.hi - 1
+            )),
+            State::Init | State::Over => None,
+        }
+    }
+
+    fn buffers(&self) -> Vec<Buffer> {
+        self.buckets.buffers(|digit| digit.to_string())
+    }
+
+    fn memory(&self) -> usize {
+        self.buckets.memory()
+    }
+
+    fn ranges(&self) -> Vec<(usize, usize)> {
+        let mut ranges: Vec<(usize, usize)> = self
+            .ranges
+            .iter()
+            .filter(|(lo, hi, _)| hi - lo > 1)
+            .map(|&(lo, hi, _)| (lo, hi - 1))
+            .collect();
+        ranges.extend(self.active_range());
+        ranges
+    }
+
+    fn active_range(&self) -> Option<(usize, usize)> {
+        match self.state {
+            State::Distributing | State::Collecting => Some((self.lo, self.hi - 1)),
+            State::Init | State::Over => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LsdRadixSort, MsdRadixSort, MAX_BASE, MIN_BASE};
+    use crate::{
+        algorithms::{
+            Buffer, Operation, Sorter, {CEIL, FLOOR, REPETITIONS, SIZE},
+        },
+        util,
+    };
+    use rand::thread_rng;
+
+    #[test]
+    fn run() {
+        for base in [MIN_BASE, 3, 10, MAX_BASE] {
+            for _ in 0..REPETITIONS {
+                let array = util::gen_random_vector(FLOOR, CEIL, SIZE, &mut thread_rng());
+                let mut expected = array.clone();
+                expected.sort();
+
+                let mut lsd = array.clone();
+                LsdRadixSort::with_base(base).run(&mut lsd);
+                assert_eq!(lsd, expected);
+
+                let mut msd = array;
+                MsdRadixSort::with_base(base).run(&mut msd);
+                assert_eq!(msd, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn buffers() {
+        let mut sorter = LsdRadixSort::new();
+        let mut array = vec![21, 13, 31];
+        sorter.step(&mut array);
+        assert_eq!(
+            sorter.operations(),
+            [Operation::Read(0), Operation::AuxWrite(0, 21)]
+        );
+        sorter.step(&mut array);
+        sorter.step(&mut array);
+        let buffers = sorter.buffers();
+        assert_eq!(buffers.len(), 10);
+        assert_eq!(buffers[1].numbers, [21, 31]);
+        assert_eq!(
+            buffers[3],
+            Buffer {
+                label: "3".to_string(),
+                numbers: vec![13],
+                highlighted: vec![],
+                cursors: vec![],
+            }
+        );
+        assert_eq!(buffers[1].highlighted, [1]);
+
+        // Collecting empties the buckets in order
+        sorter.step(&mut array);
+        assert_eq!(sorter.operations(), [Operation::Write(0, 21)]);
+        assert_eq!(sorter.buffers()[1].numbers, [31]);
+    }
+
+    #[test]
+    fn msd_ranges() {
+        let mut sorter = MsdRadixSort::new();
+        let mut array = vec![52, 17, 55, 12];
+        sorter.step(&mut array);
+        assert_eq!(sorter.operations()[0], Operation::EnterRange(0, 3));
+        while !sorter.step(&mut array) {
+            if let Some(Operation::EnterRange(lo, hi)) = sorter.operations().first() {
+                // After the tens, only the buckets with many numbers are sorted by the units
+                assert!([(0, 1), (2, 3)].contains(&(*lo, *hi)));
+            }
+        }
+        assert_eq!(array, [12, 17, 52, 55]);
+    }
+}
@@ -1,12 +1,13 @@
-use super::{Reasons, Sorter};
+use super::{Operation, Sorter};
 
+/// Finds the lowest unsorted number and switches it to the start of the unsorted numbers.
+#[derive(Clone)]
 pub struct SelectionSort {
     x: usize,
     y: usize,
     min: usize,
     needs_switch: bool,
-    special: (usize, usize),
-    reason: Reasons,
+    operations: Vec<Operation>,
 }
 
 impl Sorter for SelectionSort {
@@ -16,20 +17,16 @@ impl Sorter for SelectionSort {
             y: 1,
             min: 0,
             needs_switch: false,
-            special: (usize::MAX, usize::MAX),
-            reason: Reasons::Comparing,
+            operations: vec![],
         }
     }
 
-    fn special(&self) -> (usize, usize) {
-        self.special
-    }
-
-    fn reason(&self) -> super::Reasons {
-        self.reason
+    fn operations(&self) -> &[Operation] {
+        &self.operations
     }
 
     fn step(&mut self, array: &mut Vec<usize>) -> bool {
+        self.operations.clear();
         if self.needs_switch {
             self.switch(array);
         } else {
@@ -39,11 +36,10 @@ impl Sorter for SelectionSort {
     }
 
     fn modify_state(&mut self, array: &[usize]) -> bool {
-        if self.x == array.len() - 1 {
+        if self.x + 1 >= array.len() {
             return true;
         }
-        self.special = (self.y, self.min);
-        self.reason = Reasons::Comparing;
+        self.operations.push(Operation::Compare(self.y, self.min));
         if array[self.y] < array[self.min] {
             self.min = self.y;
         }
@@ -55,9 +51,8 @@ impl Sorter for SelectionSort {
     }
 
     fn switch(&mut self, array: &mut Vec<usize>) {
-        self.special = (self.x, self.min);
         array.swap(self.x, self.min);
-        self.reason = Reasons::Switching;
+        self.operations.push(Operation::Swap(self.x, self.min));
         self.x += 1;
         self.min = self.x;
         self.y = self.x + 1;
@@ -71,6 +66,7 @@ impl Sorter for SelectionSort {
 
 #[cfg(test)]
 mod tests {
+    use rand::thread_rng;
 
     use super::SelectionSort;
     use crate::{
@@ -84,7 +80,7 @@ mod tests {
     fn run() {
         for _ in 0..REPETITIONS {
             let mut sorter = SelectionSort::new();
-            let mut array = util::gen_random_vector(FLOOR, CEIL, SIZE);
+            let mut array = util::gen_random_vector(FLOOR, CEIL, SIZE, &mut thread_rng());
 
             let mut expected = array.clone();
             expected.sort();
@@ -0,0 +1,231 @@
+use super::{Operation, Sorter};
+use strum_macros::{EnumIter, EnumString};
+
+/// Ciura's gaps, found empirically. Larger gaps are extended by this factor.
+const CIURA: [usize; 9] = [1, 4, 10, 23, 57, 132, 301, 701, 1750];
+const CIURA_FACTOR: f64 = 2.25;
+
+/// The gaps ShellSort goes through, from the largest to 1.
+/// Names are parsed ignoring case, e.g. `"knuth"` is `GapSequence::Knuth`.
+#[derive(PartialEq, Eq, Debug, Default, EnumIter, EnumString, Clone, Copy)]
+#[strum(ascii_case_insensitive)]
+pub enum GapSequence {
+    /// Shell's original gaps, halving the size each time: n/2, n/4, ..., 1.
+    Shell,
+    /// Knuth's (3^k - 1) / 2: 1, 4, 13, 40, ...
+    Knuth,
+    /// Sedgewick's 4^k + 3 * 2^(k-1) + 1: 1, 8, 23, 77, ...
+    Sedgewick,
+    /// Ciura's empirical gaps: 1, 4, 10, 23, 57, ...
+    #[default]
+    Ciura,
+    /// Tokuda's ceil((9 (9/4)^(k-1) - 4) / 5): 1, 4, 9, 20, 46, ...
+    Tokuda,
+}
+
+impl GapSequence {
+    /// Returns the gaps used to sort `n` numbers, from the largest to 1.
+    /// There are no gaps if there's nothing to sort.
+    pub fn gaps(&self, n: usize) -> Vec<usize> {
+        if n < 2 {
+            return vec![];
+        }
+        let mut gaps = match self {
+            GapSequence::Shell => {
+                let mut gaps = vec![];
+                let mut gap = n / 2;
+                while gap > 0 {
+                    gaps.push(gap);
+                    gap /= 2;
+                }
+                gaps.reverse();
+                gaps
+            }
+            GapSequence::Knuth => ascending(n, |k, previous| match k {
+                0 => 1,
+                _ => 3 * previous + 1,
+            }),
+            GapSequence::Sedgewick => ascending(n, |k, _| match k {
+                0 => 1,
+                _ => 4_usize.pow(k as u32) + 3 * 2_usize.pow(k as u32 - 1) + 1,
+            }),
+            GapSequence::Ciura => ascending(n, |k, previous| match CIURA.get(k) {
+                Some(&gap) => gap,
+                None => (previous as f64 * CIURA_FACTOR) as usize,
+            }),
+            GapSequence::Tokuda => ascending(n, |k, _| {
+                let gap = (9. * 2.25_f64.powi(k as i32) - 4.) / 5.;
+                gap.ceil() as usize
+            }),
+        };
+        gaps.reverse();
+        gaps
+    }
+}
+
+/// Returns the gaps lower than `n`, in ascending order,
+/// where `next` computes the k-th gap from the previous one.
+fn ascending(n: usize, next: impl Fn(usize, usize) -> usize) -> Vec<usize> {
+    let mut gaps = vec![];
+    let mut gap = next(0, 0);
+    while gap < n {
+        gaps.push(gap);
+        gap = next(gaps.len(), gap);
+    }
+    gaps
+}
+
+/// Insertion sorts the numbers that are a gap apart, for ever smaller gaps.
+#[derive(Clone)]
+pub struct ShellSort {
+    operations: Vec<Operation>,
+    sequence: GapSequence,
+    /// The gaps left to go through, with the current one last.
+    gaps: Vec<usize>,
+    started: bool,
+    /// The number being inserted among the ones a gap apart before it.
+    curr: usize,
+    /// The position of the number being inserted.
+    y: usize,
+    needs_switch: bool,
+    switched: bool,
+}
+
+impl ShellSort {
+    /// Creates a ShellSort that goes through the given gaps.
+    pub fn with_gaps(sequence: GapSequence) -> ShellSort {
+        ShellSort {
+            sequence,
+            ..ShellSort::new()
+        }
+    }
+
+    fn gap(&self) -> usize {
+        self.gaps.last().copied().unwrap_or(1)
+    }
+}
+
+impl Sorter for ShellSort {
+    fn new() -> ShellSort {
+        ShellSort {
+            operations: vec![],
+            sequence: GapSequence::default(),
+            gaps: vec![],
+            started: false,
+            curr: 0,
+            y: 0,
+            needs_switch: false,
+            switched: false,
+        }
+    }
+
+    fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    fn step(&mut self, array: &mut Vec<usize>) -> bool {
+        self.operations.clear();
+        if self.needs_switch {
+            self.switch(array);
+        } else {
+            return self.modify_state(array);
+        }
+        false
+    }
+
+    fn modify_state(&mut self, array: &[usize]) -> bool {
+        if !self.started {
+            self.started = true;
+            // The gaps are kept reversed, so the current one can be popped when done
+            self.gaps = self.sequence.gaps(array.len());
+            self.gaps.reverse();
+            self.curr = self.gap();
+            self.y = self.curr;
+        } else if self.switched && self.y >= 2 * self.gap() {
+            self.y -= self.gap();
+        } else {
+            self.curr += 1;
+            if self.curr >= array.len() {
+                self.gaps.pop();
+                self.curr = self.gap();
+            }
+            self.y = self.curr;
+        }
+        self.switched = false;
+        if self.gaps.is_empty() {
+            return true;
+        }
+        let x = self.y - self.gap();
+        self.operations.push(Operation::Compare(x, self.y));
+        self.needs_switch = array[self.y] < array[x];
+        false
+    }
+
+    fn switch(&mut self, array: &mut Vec<usize>) {
+        let x = self.y - self.gap();
+        array.swap(x, self.y);
+        self.operations.push(Operation::Swap(x, self.y));
+        self.needs_switch = false;
+        self.switched = true;
+    }
+
+    fn reset_state(&mut self) {
+        *self = ShellSort::with_gaps(self.sequence);
+    }
+
+    fn status(&self) -> Option<String> {
+        self.gaps.last().map(|gap| format!("Gap: {gap}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GapSequence, ShellSort};
+    use crate::{
+        algorithms::{
+            Sorter, {CEIL, FLOOR, REPETITIONS, SIZE},
+        },
+        util,
+    };
+    use rand::thread_rng;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn run() {
+        for sequence in GapSequence::iter() {
+            for _ in 0..REPETITIONS {
+                let mut sorter = ShellSort::with_gaps(sequence);
+                let mut array = util::gen_random_vector(FLOOR, CEIL, SIZE, &mut thread_rng());
+                let mut expected = array.clone();
+                expected.sort();
+                sorter.run(&mut array);
+                assert_eq!(array, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn gaps() {
+        assert_eq!(GapSequence::Shell.gaps(20), [10, 5, 2, 1]);
+        assert_eq!(GapSequence::Knuth.gaps(50), [40, 13, 4, 1]);
+        assert_eq!(GapSequence::Sedgewick.gaps(100), [77, 23, 8, 1]);
+        assert_eq!(GapSequence::Ciura.gaps(60), [57, 23, 10, 4, 1]);
+        assert_eq!(GapSequence::Ciura.gaps(4000)[0], 3937);
+        assert_eq!(GapSequence::Tokuda.gaps(50), [46, 20, 9, 4, 1]);
+        for sequence in GapSequence::iter() {
+            assert!(sequence.gaps(1).is_empty());
+            assert_eq!(sequence.gaps(2), [1]);
+        }
+    }
+
+    #[test]
+    fn status() {
+        let mut sorter = ShellSort::with_gaps(GapSequence::Shell);
+        let mut array = vec![4, 3, 2, 1];
+        assert_eq!(sorter.status(), None);
+        sorter.step(&mut array);
+        assert_eq!(sorter.status(), Some("Gap: 2".to_string()));
+        sorter.run(&mut array);
+        assert_eq!(array, [1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,80 @@
+use super::{Operation, Sorter};
+
+/// Counts the operations a Sorter has performed so far.
+#[derive(PartialEq, Eq, Debug, Default, Clone, Copy)]
+pub struct Stats {
+    /// Steps taken.
+    pub steps: usize,
+    /// Numbers compared.
+    pub comparisons: usize,
+    /// Numbers switched.
+    pub swaps: usize,
+    /// Numbers written to the array.
+    pub writes: usize,
+    /// Numbers written to auxiliary buffers.
+    pub aux_writes: usize,
+    /// Most numbers held outside of the array at once.
+    pub aux_memory: usize,
+}
+
+impl Stats {
+    /// Accounts for a single step that performed `operations`.
+    pub fn record(&mut self, operations: &[Operation]) {
+        self.steps += 1;
+        for operation in operations {
+            match operation {
+                Operation::Compare(..) => self.comparisons += 1,
+                Operation::Swap(..) => self.swaps += 1,
+                Operation::Write(..) => self.writes += 1,
+                Operation::AuxWrite(..) => self.aux_writes += 1,
+                Operation::Read(..)
+                | Operation::MarkPivot(..)
+                | Operation::MarkBoundary(..)
+                | Operation::EnterRange(..) => {}
+            }
+        }
+    }
+
+    /// Accounts for the step `sorter` has just taken, along with the numbers it now holds.
+    pub fn track<S: Sorter + ?Sized>(&mut self, sorter: &S) {
+        self.record(sorter.operations());
+        self.aux_memory = self.aux_memory.max(sorter.memory());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Stats;
+    use crate::algorithms::{merge_sort::MergeSort, Operation, Sorter};
+
+    #[test]
+    fn record() {
+        let mut stats = Stats::default();
+        stats.record(&[Operation::Compare(0, 1), Operation::Swap(0, 1)]);
+        stats.record(&[Operation::Read(2), Operation::AuxWrite(0, 4)]);
+        stats.record(&[Operation::Write(3, 4), Operation::MarkPivot(1)]);
+        let expected = Stats {
+            steps: 3,
+            comparisons: 1,
+            swaps: 1,
+            writes: 1,
+            aux_writes: 1,
+            aux_memory: 0,
+        };
+        assert_eq!(stats, expected);
+    }
+
+    #[test]
+    fn run() {
+        let mut array = vec![2, 1, 0];
+        let stats = MergeSort::new().run(&mut array);
+        assert_eq!(array, vec![0, 1, 2]);
+        // Each merge writes every number of its slice to the buffer and back to the array
+        // The slices are [2, 1], [0] and then [1, 2, 0]
+        assert_eq!(stats.aux_writes, 6);
+        assert_eq!(stats.writes, 6);
+        assert_eq!(stats.swaps, 0);
+        // The buffer is a copy of the whole array
+        assert_eq!(stats.aux_memory, 3);
+    }
+}
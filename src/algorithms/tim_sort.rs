@@ -0,0 +1,638 @@
+use super::{Buffer, Operation, Sorter};
+
+/// Arrays shorter than this are a single run. Real implementations use 32 or 64,
+/// a smaller one keeps many runs visible on arrays small enough to be shown.
+/// The thresholds of the other hybrid sorts are kept small for the same reason.
+pub(super) const MIN_MERGE: usize = 8;
+/// How many times in a row a run must win while merging before galloping through it.
+/// Real implementations start from 7, see [`MIN_MERGE`] for why it's smaller.
+const MIN_GALLOP: usize = 3;
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+enum State {
+    Init,
+    /// Looking for the next natural run.
+    StartingRun,
+    /// Comparing neighbours to see how far the natural run goes.
+    FindingRun,
+    /// Deciding what to do with the natural run that was found.
+    FoundRun,
+    /// Switching the ends of a descending run until it's ascending.
+    Reversing,
+    /// Growing a short run up to the minimum size.
+    Extending,
+    /// Looking for where the number after the run goes, with a binary search.
+    Searching,
+    /// Switching the number after the run backwards until it's where it goes.
+    Inserting,
+    /// Merging runs until the stack of runs holds its invariants again.
+    Collapsing,
+    /// Merging two runs, one number at a time.
+    Merging,
+    /// Searching for how many numbers of a run go before the head of the other one.
+    Galloping,
+    /// Copying the numbers found by galloping into the buffer.
+    GallopCopying,
+    /// Copying the numbers left of either run into the buffer.
+    Draining,
+    /// Copying the buffer back to the array.
+    WritingBack,
+    Over,
+}
+
+/// Finds natural runs, extends the short ones with binary insertion
+/// and merges them, galloping through runs that keep winning.
+#[derive(Clone)]
+pub struct TimSort {
+    operations: Vec<Operation>,
+    state: State,
+    min_run: usize,
+    /// Runs found so far, as their start and length, waiting to be merged.
+    runs: Vec<(usize, usize)>,
+    /// Whether every run has been found and only merging is left.
+    force: bool,
+    /// Where the run being found starts.
+    start: usize,
+    /// Where the run being found ends (exclusive).
+    end: usize,
+    descending: bool,
+    /// Ends of a descending run being reversed.
+    a: usize,
+    b: usize,
+    /// How far the run is extended to.
+    target: usize,
+    /// Bounds of the binary search of where a number goes (`hi` exclusive).
+    search_lo: usize,
+    search_hi: usize,
+    /// Position of the number being inserted into the run.
+    y: usize,
+    /// Which entry of `runs` is being merged with the one after it.
+    merging: usize,
+    /// The runs being merged are from `lo` to `mid` and from `mid` to `hi` (exclusive).
+    lo: usize,
+    mid: usize,
+    hi: usize,
+    /// Heads of the runs being merged.
+    i: usize,
+    j: usize,
+    temp: Vec<usize>,
+    wins_left: usize,
+    wins_right: usize,
+    /// Whether the left run is the one galloping.
+    gallop_left: bool,
+    /// Bounds of the gallop, numbers before `gallop_lo` go first and from `gallop_hi` don't.
+    gallop_lo: usize,
+    gallop_hi: usize,
+    gallop_step: usize,
+    exponential: bool,
+    /// Numbers left to be copied after galloping.
+    count: usize,
+    /// Where the buffer is being written back to.
+    k: usize,
+    /// How many numbers are being sorted, known from the first step.
+    len: usize,
+}
+
+/// Returns the minimum length of a run, so that splitting the array into runs of about
+/// that length gives a power of 2 of them, or slightly less, which merge evenly.
+fn min_run(mut n: usize) -> usize {
+    let mut r = 0;
+    while n >= MIN_MERGE {
+        r |= n & 1;
+        n >>= 1;
+    }
+    n + r
+}
+
+impl TimSort {
+    /// Decides which run, if any, should be merged with the next one
+    /// for the stack of runs to keep its invariants.
+    fn merge_at(&self) -> Option<usize> {
+        let len = |i: usize| self.runs[i].1;
+        let n = self.runs.len();
+        if self.force {
+            return match n {
+                0 | 1 => None,
+                2 => Some(0),
+                _ if len(n - 3) < len(n - 1) => Some(n - 3),
+                _ => Some(n - 2),
+            };
+        }
+        // Every run must be longer than the two above it, and longer than the one above it
+        let broken = (n >= 3 && len(n - 3) <= len(n - 2) + len(n - 1))
+            || (n >= 4 && len(n - 4) <= len(n - 3) + len(n - 2));
+        if broken {
+            if len(n - 3) < len(n - 1) {
+                Some(n - 3)
+            } else {
+                Some(n - 2)
+            }
+        } else if n >= 2 && len(n - 2) <= len(n - 1) {
+            Some(n - 2)
+        } else {
+            None
+        }
+    }
+
+    /// Copies the number at `index` to the end of the buffer.
+    fn take(&mut self, array: &[usize], index: usize) {
+        self.temp.push(array[index]);
+        self.operations.push(Operation::Read(index));
+        let k = self.lo + self.temp.len() - 1;
+        self.operations.push(Operation::AuxWrite(k, array[index]));
+    }
+}
+
+impl Sorter for TimSort {
+    fn new() -> TimSort {
+        TimSort {
+            operations: vec![],
+            state: State::Init,
+            min_run: 1,
+            runs: vec![],
+            force: false,
+            start: 0,
+            end: 0,
+            descending: false,
+            a: 0,
+            b: 0,
+            target: 0,
+            search_lo: 0,
+            search_hi: 0,
+            y: 0,
+            merging: 0,
+            lo: 0,
+            mid: 0,
+            hi: 0,
+            i: 0,
+            j: 0,
+            temp: vec![],
+            wins_left: 0,
+            wins_right: 0,
+            gallop_left: false,
+            gallop_lo: 0,
+            gallop_hi: 0,
+            gallop_step: 1,
+            exponential: true,
+            count: 0,
+            k: 0,
+            len: 0,
+        }
+    }
+
+    fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    fn step(&mut self, array: &mut Vec<usize>) -> bool {
+        self.operations.clear();
+        if self.modify_state(array) {
+            return true;
+        }
+        self.switch(array);
+        false
+    }
+
+    fn modify_state(&mut self, array: &[usize]) -> bool {
+        let n = array.len();
+        loop {
+            match self.state {
+                State::Init => {
+                    self.len = n;
+                    self.min_run = min_run(n);
+                    self.state = State::StartingRun;
+                }
+                State::StartingRun => {
+                    if self.start >= n {
+                        self.force = true;
+                        self.state = State::Collapsing;
+                    } else {
+                        self.end = self.start + 1;
+                        self.descending = false;
+                        self.state = State::FindingRun;
+                    }
+                }
+                State::FindingRun => {
+                    if self.end < n {
+                        return false;
+                    }
+                    self.state = State::FoundRun;
+                }
+                State::FoundRun => {
+                    self.target = n.min(self.start + self.min_run);
+                    if self.descending {
+                        self.a = self.start;
+                        self.b = self.end - 1;
+                        self.state = State::Reversing;
+                    } else {
+                        self.state = State::Extending;
+                    }
+                }
+                State::Reversing => {
+                    if self.a < self.b {
+                        return false;
+                    }
+                    self.state = State::Extending;
+                }
+                State::Extending => {
+                    if self.end >= self.target {
+                        self.runs.push((self.start, self.end - self.start));
+                        self.start = self.end;
+                        self.state = State::Collapsing;
+                    } else {
+                        self.search_lo = self.start;
+                        self.search_hi = self.end;
+                        self.state = State::Searching;
+                    }
+                }
+                State::Searching => {
+                    if self.search_lo < self.search_hi {
+                        return false;
+                    }
+                    self.y = self.end;
+                    self.state = State::Inserting;
+                }
+                State::Inserting => {
+                    if self.y > self.search_lo {
+                        return false;
+                    }
+                    self.end += 1;
+                    self.state = State::Extending;
+                }
+                State::Collapsing => match self.merge_at() {
+                    Some(at) => {
+                        self.merging = at;
+                        self.lo = self.runs[at].0;
+                        self.mid = self.lo + self.runs[at].1;
+                        self.hi = self.mid + self.runs[at + 1].1;
+                        self.i = self.lo;
+                        self.j = self.mid;
+                        self.temp.clear();
+                        self.wins_left = 0;
+                        self.wins_right = 0;
+                        self.operations
+                            .push(Operation::EnterRange(self.lo, self.hi - 1));
+                        self.state = State::Merging;
+                    }
+                    None if self.force => self.state = State::Over,
+                    None => self.state = State::StartingRun,
+                },
+                State::Merging | State::Galloping => {
+                    if self.i >= self.mid || self.j >= self.hi {
+                        self.state = State::Draining;
+                    } else if self.state == State::Galloping && self.gallop_lo >= self.gallop_hi {
+                        self.count = self.gallop_lo;
+                        self.state = State::GallopCopying;
+                    } else {
+                        return false;
+                    }
+                }
+                State::GallopCopying => {
+                    if self.count > 0 {
+                        return false;
+                    }
+                    self.state = State::Merging;
+                }
+                State::Draining => {
+                    if self.i < self.mid || self.j < self.hi {
+                        return false;
+                    }
+                    self.k = self.lo;
+                    self.state = State::WritingBack;
+                }
+                State::WritingBack => {
+                    if self.k < self.hi {
+                        return false;
+                    }
+                    self.runs[self.merging].1 = self.hi - self.lo;
+                    self.runs.remove(self.merging + 1);
+                    self.temp.clear();
+                    self.state = State::Collapsing;
+                }
+                State::Over => return true,
+            }
+        }
+    }
+
+    fn switch(&mut self, array: &mut Vec<usize>) {
+        match self.state {
+            State::FindingRun => {
+                let (x, y) = (self.end - 1, self.end);
+                self.operations.push(Operation::Compare(x, y));
+                // The first two numbers decide the direction of the run,
+                // descending runs must be strictly so to be reversed without breaking stability
+                if self.end == self.start + 1 {
+                    self.descending = array[y] < array[x];
+                } else if self.descending != (array[y] < array[x]) {
+                    self.state = State::FoundRun;
+                    return;
+                }
+                self.end += 1;
+            }
+            State::Reversing => {
+                array.swap(self.a, self.b);
+                self.operations.push(Operation::Swap(self.a, self.b));
+                self.a += 1;
+                self.b -= 1;
+            }
+            State::Searching => {
+                let middle = (self.search_lo + self.search_hi) / 2;
+                self.operations.push(Operation::Compare(middle, self.end));
+                if array[self.end] < array[middle] {
+                    self.search_hi = middle;
+                } else {
+                    self.search_lo = middle + 1;
+                }
+            }
+            State::Inserting => {
+                array.swap(self.y - 1, self.y);
+                self.operations.push(Operation::Swap(self.y - 1, self.y));
+                self.y -= 1;
+            }
+            State::Merging => {
+                self.operations.push(Operation::Compare(self.i, self.j));
+                // Ties go to the left run, which keeps the sort stable
+                if array[self.j] < array[self.i] {
+                    self.take(array, self.j);
+                    self.j += 1;
+                    self.wins_right += 1;
+                    self.wins_left = 0;
+                } else {
+                    self.take(array, self.i);
+                    self.i += 1;
+                    self.wins_left += 1;
+                    self.wins_right = 0;
+                }
+                if self.wins_left >= MIN_GALLOP || self.wins_right >= MIN_GALLOP {
+                    self.gallop_left = self.wins_left >= MIN_GALLOP;
+                    self.gallop_lo = 0;
+                    self.gallop_hi = if self.gallop_left {
+                        self.mid - self.i
+                    } else {
+                        self.hi - self.j
+                    };
+                    self.gallop_step = 1;
+                    self.exponential = true;
+                    self.wins_left = 0;
+                    self.wins_right = 0;
+                    self.state = State::Galloping;
+                }
+            }
+            State::Galloping => {
+                // Probe 1, 3, 7... numbers ahead until overshooting, then binary search back
+                let offset = if self.exponential {
+                    (self.gallop_lo + self.gallop_step - 1).min(self.gallop_hi - 1)
+                } else {
+                    (self.gallop_lo + self.gallop_hi) / 2
+                };
+                let goes_first = if self.gallop_left {
+                    self.operations
+                        .push(Operation::Compare(self.i + offset, self.j));
+                    array[self.i + offset] <= array[self.j]
+                } else {
+                    self.operations
+                        .push(Operation::Compare(self.j + offset, self.i));
+                    array[self.j + offset] < array[self.i]
+                };
+                if goes_first {
+                    self.gallop_lo = offset + 1;
+                    self.gallop_step *= 2;
+                } else {
+                    self.gallop_hi = offset;
+                    self.exponential = false;
+                }
+            }
+            State::GallopCopying => {
+                if self.gallop_left {
+                    self.take(array, self.i);
+                    self.i += 1;
+                } else {
+                    self.take(array, self.j);
+                    self.j += 1;
+                }
+                self.count -= 1;
+            }
+            State::Draining => {
+                if self.i < self.mid {
+                    self.take(array, self.i);
+                    self.i += 1;
+                } else {
+                    self.take(array, self.j);
+                    self.j += 1;
+                }
+            }
+            State::WritingBack => {
+                let value = self.temp[self.k - self.lo];
+                array[self.k] = value;
+                self.operations.push(Operation::Write(self.k, value));
+                self.k += 1;
+            }
+            State::Init
+            | State::StartingRun
+            | State::FoundRun
+            | State::Extending
+            | State::Collapsing
+            | State::Over => {}
+        }
+    }
+
+    fn reset_state(&mut self) {
+        *self = TimSort::new();
+    }
+
+    fn status(&self) -> Option<String> {
+        let status = match self.state {
+            State::FindingRun => format!("Finding the run starting at {}", self.start),
+            State::Reversing => format!("Reversing the descending run at {}", self.start),
+            State::Searching | State::Inserting => format!(
+                "Extending the run at {} to {} with binary insertion",
+                self.start,
+                self.target - 1
+            ),
+            State::Merging | State::Draining | State::WritingBack => {
+                format!("Merging the runs at {} and {}", self.lo, self.mid)
+            }
+            State::Galloping | State::GallopCopying => {
+                let run = if self.gallop_left { self.lo } else { self.mid };
+                format!("Galloping through the run at {run}")
+            }
+            _ => return None,
+        };
+        Some(status)
+    }
+
+    /// The buffer lines up with the array, with the numbers merged so far
+    /// where they'll be written back to.
+    fn buffers(&self) -> Vec<Buffer> {
+        let k = match self.state {
+            State::Merging | State::Galloping | State::GallopCopying | State::Draining => {
+                self.lo + self.temp.len()
+            }
+            State::WritingBack => self.k,
+            _ => return vec![],
+        };
+        let mut numbers = vec![0; self.len];
+        numbers[self.lo..self.lo + self.temp.len()].copy_from_slice(&self.temp);
+        vec![Buffer {
+            label: "Merge buffer".to_string(),
+            numbers,
+            highlighted: match self.operations.last() {
+                Some(&Operation::AuxWrite(k, _)) => vec![k],
+                _ => vec![],
+            },
+            cursors: if k < self.hi {
+                vec![("k".to_string(), k)]
+            } else {
+                vec![]
+            },
+        }]
+    }
+
+    fn cursors(&self) -> Vec<(String, usize)> {
+        match self.state {
+            State::Merging | State::Galloping | State::GallopCopying | State::Draining => {
+                let mut cursors = vec![];
+                if self.i < self.mid {
+                    cursors.push(("i".to_string(), self.i));
+                }
+                if self.j < self.hi {
+                    cursors.push(("j".to_string(), self.j));
+                }
+                cursors
+            }
+            _ => vec![],
+        }
+    }
+
+    /// The buffer is drawn as long as the array, but only holds the numbers being merged.
+    fn memory(&self) -> usize {
+        match self.state {
+            State::Merging
+            | State::Galloping
+            | State::GallopCopying
+            | State::Draining
+            | State::WritingBack => self.temp.len(),
+            _ => 0,
+        }
+    }
+
+    fn ranges(&self) -> Vec<(usize, usize)> {
+        let mut ranges: Vec<(usize, usize)> = self
+            .runs
+            .iter()
+            .map(|&(start, len)| (start, start + len - 1))
+            .collect();
+        if let State::FindingRun
+        | State::FoundRun
+        | State::Reversing
+        | State::Extending
+        | State::Searching
+        | State::Inserting = self.state
+        {
+            ranges.push((self.start, self.end - 1));
+        }
+        ranges
+    }
+
+    fn active_range(&self) -> Option<(usize, usize)> {
+        match self.state {
+            State::FindingRun
+            | State::FoundRun
+            | State::Reversing
+            | State::Extending
+            | State::Searching
+            | State::Inserting => Some((self.start, self.end - 1)),
+            State::Merging
+            | State::Galloping
+            | State::GallopCopying
+            | State::Draining
+            | State::WritingBack => Some((self.lo, self.hi - 1)),
+            State::Init | State::StartingRun | State::Collapsing | State::Over => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{min_run, TimSort};
+    use crate::{
+        algorithms::{
+            Operation, Sorter, {CEIL, FLOOR, REPETITIONS, SIZE},
+        },
+        util,
+    };
+    use rand::thread_rng;
+
+    #[test]
+    fn run() {
+        for size in [SIZE, 100] {
+            for _ in 0..REPETITIONS {
+                let mut sorter = TimSort::new();
+                let mut array = util::gen_random_vector(FLOOR, CEIL, size, &mut thread_rng());
+                let mut expected = array.clone();
+                expected.sort();
+                sorter.run(&mut array);
+                assert_eq!(array, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn runs() {
+        assert_eq!(min_run(7), 7);
+        assert_eq!(min_run(20), 5);
+        assert_eq!(min_run(64), 4);
+
+        // A descending run followed by an ascending one, each long enough on its own
+        let mut array = vec![9, 8, 7, 6, 5, 4, 5, 6, 7, 8, 9, 10];
+        let mut sorter = TimSort::new();
+        let mut merged = false;
+        while !sorter.step(&mut array) {
+            if sorter.operations().first() == Some(&Operation::EnterRange(0, 11)) {
+                merged = true;
+                assert_eq!(array, [4, 5, 6, 7, 8, 9, 5, 6, 7, 8, 9, 10]);
+                assert_eq!(sorter.ranges(), [(0, 5), (6, 11)]);
+                // The buffer lines up with the merged runs, the heads of which are pointed at
+                let buffer = &sorter.buffers()[0];
+                assert_eq!(buffer.numbers, [4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+                assert_eq!(buffer.highlighted, [0]);
+                assert_eq!(buffer.cursors, [("k".to_string(), 1)]);
+                assert_eq!(
+                    sorter.cursors(),
+                    [("i".to_string(), 1), ("j".to_string(), 6)]
+                );
+            }
+        }
+        assert!(merged);
+        assert_eq!(array, [4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10]);
+    }
+
+    #[test]
+    fn gallop() {
+        // The left run wins every comparison, so most of it is galloped through
+        let mut array = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 0, 20, 21];
+        let mut sorter = TimSort::new();
+        let mut galloped = false;
+        while !sorter.step(&mut array) {
+            galloped |= sorter
+                .status()
+                .is_some_and(|status| status.starts_with("Galloping"));
+        }
+        assert!(galloped);
+        assert!(array.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn extending() {
+        // Runs are at least 5 numbers long, so the short run at 5 is extended up to 9
+        let mut array: Vec<usize> = (0..20).rev().collect();
+        array[..5].sort();
+        array[5..7].sort();
+        let mut sorter = TimSort::new();
+        let mut statuses = vec![];
+        while !sorter.step(&mut array) {
+            statuses.extend(sorter.status());
+        }
+        assert!(statuses.contains(&"Extending the run at 5 to 9 with binary insertion".to_string()));
+    }
+}
@@ -0,0 +1,183 @@
+use super::{merge_sort::Merge, Buffer, Operation, Sorter};
+
+/// What is left to do of a call of a recursive merge sort.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub(super) enum Task {
+    /// Sorting the numbers from the first position to the last.
+    Sort(usize, usize),
+    /// Merging the sorted numbers from the first position to the one before the second
+    /// with the ones from the second to the third.
+    Merge(usize, usize, usize),
+}
+
+impl Task {
+    /// Returns the first and last positions the task covers.
+    pub(super) fn range(&self) -> (usize, usize) {
+        match *self {
+            Task::Sort(lo, hi) | Task::Merge(lo, _, hi) => (lo, hi),
+        }
+    }
+}
+
+/// Pops tasks off `stack`, splitting the slices to sort in halves, until a merge is due.
+/// Returns the first, middle and last positions of the merge, or `None` once the stack is empty.
+pub(super) fn next_merge(stack: &mut Vec<Task>) -> Option<(usize, usize, usize)> {
+    while let Some(task) = stack.pop() {
+        match task {
+            Task::Sort(lo, hi) if lo < hi => {
+                let len = hi - lo + 1;
+                let mid = lo + len / 2;
+                // The left half is on top, so it's sorted first
+                stack.push(Task::Merge(lo, mid, hi));
+                stack.push(Task::Sort(mid, hi));
+                stack.push(Task::Sort(lo, mid - 1));
+            }
+            Task::Sort(..) => {}
+            Task::Merge(lo, mid, hi) => return Some((lo, mid, hi)),
+        }
+    }
+    None
+}
+
+/// Sorts each half of the array before merging them, splitting the halves the same way,
+/// using a buffer as long as the slices being merged.
+#[derive(Clone)]
+pub struct TopDownMergeSort {
+    /// The calls yet to be finished, the innermost on top.
+    stack: Vec<Task>,
+    merge: Option<Merge>,
+    started: bool,
+    operations: Vec<Operation>,
+    /// How many numbers are being sorted, known from the first step.
+    len: usize,
+}
+
+impl Sorter for TopDownMergeSort {
+    fn new() -> Self {
+        TopDownMergeSort {
+            stack: vec![],
+            merge: None,
+            started: false,
+            operations: vec![],
+            len: 0,
+        }
+    }
+
+    fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    fn step(&mut self, array: &mut Vec<usize>) -> bool {
+        self.operations.clear();
+        self.len = array.len();
+        if !self.started {
+            self.started = true;
+            if self.len > 1 {
+                self.stack.push(Task::Sort(0, self.len - 1));
+            }
+        }
+        if self.modify_state(array) {
+            return true;
+        }
+        self.switch(array);
+        false
+    }
+
+    fn modify_state(&mut self, _array: &[usize]) -> bool {
+        if self.merge.is_none() {
+            self.merge = next_merge(&mut self.stack).map(|(lo, mid, hi)| Merge::new(lo, mid, hi));
+        }
+        self.merge.is_none()
+    }
+
+    fn switch(&mut self, array: &mut Vec<usize>) {
+        if let Some(merge) = &mut self.merge {
+            if merge.step(array, &mut self.operations) {
+                self.merge = None;
+            }
+        }
+    }
+
+    fn reset_state(&mut self) {
+        *self = TopDownMergeSort::new();
+    }
+
+    /// The slices of the calls yet to be finished, the one being merged last.
+    fn ranges(&self) -> Vec<(usize, usize)> {
+        self.stack
+            .iter()
+            .map(Task::range)
+            .chain(self.active_range())
+            .collect()
+    }
+
+    fn active_range(&self) -> Option<(usize, usize)> {
+        self.merge.as_ref().map(|merge| (merge.lo, merge.hi))
+    }
+
+    fn buffers(&self) -> Vec<Buffer> {
+        self.merge
+            .iter()
+            .map(|merge| merge.buffer(self.len, &self.operations))
+            .collect()
+    }
+
+    fn cursors(&self) -> Vec<(String, usize)> {
+        self.merge.as_ref().map(Merge::cursors).unwrap_or_default()
+    }
+
+    fn memory(&self) -> usize {
+        self.merge.as_ref().map_or(0, Merge::memory)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{next_merge, Task, TopDownMergeSort};
+    use crate::{
+        algorithms::{
+            Sorter, {CEIL, FLOOR, REPETITIONS, SIZE},
+        },
+        util,
+    };
+    use rand::thread_rng;
+
+    #[test]
+    fn run() {
+        for _ in 0..REPETITIONS {
+            let mut sorter = TopDownMergeSort::new();
+            let mut array = util::gen_random_vector(FLOOR, CEIL, SIZE, &mut thread_rng());
+
+            let mut expected = array.clone();
+            expected.sort();
+
+            let stats = sorter.run(&mut array);
+
+            assert_eq!(array, expected);
+            // The last merge is the longest, holding every number
+            assert_eq!(stats.aux_memory, SIZE);
+        }
+    }
+
+    #[test]
+    fn recursion() {
+        let mut stack = vec![Task::Sort(0, 4)];
+        // Splitting down to the first pair, the rest of the calls waiting beneath it
+        assert_eq!(next_merge(&mut stack), Some((0, 1, 1)));
+        assert_eq!(stack, [Task::Merge(0, 2, 4), Task::Sort(2, 4)]);
+        assert_eq!(next_merge(&mut stack), Some((3, 4, 4)));
+        assert_eq!(next_merge(&mut stack), Some((2, 3, 4)));
+        assert_eq!(next_merge(&mut stack), Some((0, 2, 4)));
+        assert_eq!(next_merge(&mut stack), None);
+    }
+
+    #[test]
+    fn ranges() {
+        let mut sorter = TopDownMergeSort::new();
+        let mut array = vec![4, 3, 2, 1, 0];
+        sorter.step(&mut array);
+        assert_eq!(sorter.active_range(), Some((0, 1)));
+        assert_eq!(sorter.ranges(), [(0, 4), (2, 4), (0, 1)]);
+        assert_eq!(sorter.memory(), 2);
+    }
+}
@@ -0,0 +1,253 @@
+use crate::algorithms::{options::Options, stats::Stats, Algorithms};
+use crate::generator::Distribution;
+use rand::{rngs::StdRng, SeedableRng};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+/// How fast a count may grow with the size of the array.
+#[derive(PartialEq, Eq, Debug, EnumIter, Clone, Copy)]
+pub enum Model {
+    /// O(n)
+    Linear,
+    /// O(n log n)
+    Linearithmic,
+    /// O(n²)
+    Quadratic,
+}
+
+impl Model {
+    /// Returns the growth of the model for an array of size `n`, without any constant factor.
+    pub fn growth(&self, n: f64) -> f64 {
+        match self {
+            Model::Linear => n,
+            Model::Linearithmic => n * n.max(1.).log2(),
+            Model::Quadratic => n * n,
+        }
+    }
+
+    /// Returns the model in big O notation.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Model::Linear => "O(n)",
+            Model::Linearithmic => "O(n log n)",
+            Model::Quadratic => "O(n²)",
+        }
+    }
+}
+
+/// The operation counted when analysing a run.
+#[derive(PartialEq, Eq, Debug, EnumIter, Clone, Copy)]
+pub enum Metric {
+    /// Steps taken.
+    Steps,
+    /// Numbers compared.
+    Comparisons,
+    /// Numbers switched.
+    Swaps,
+    /// Numbers written, to the array or to auxiliary buffers.
+    Writes,
+}
+
+impl Metric {
+    /// Returns the count of this metric in `stats`.
+    pub fn count(&self, stats: &Stats) -> usize {
+        match self {
+            Metric::Steps => stats.steps,
+            Metric::Comparisons => stats.comparisons,
+            Metric::Swaps => stats.swaps,
+            Metric::Writes => stats.writes + stats.aux_writes,
+        }
+    }
+}
+
+/// The operations counted in a run over an array of a given size.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Sample {
+    /// How many numbers were sorted.
+    pub size: usize,
+    /// The shape of the numbers sorted.
+    pub distribution: Distribution,
+    /// The operations counted in the run.
+    pub stats: Stats,
+}
+
+/// How well a model describes a set of counts.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Fit {
+    /// The model fitted.
+    pub model: Model,
+    /// The constant factor that brings the model closest to the counts.
+    pub constant: f64,
+    /// Root mean square of the differences between the model and the counts,
+    /// relative to the mean count. Lower is better.
+    pub error: f64,
+}
+
+impl Fit {
+    /// Returns the count the fitted model predicts for an array of size `n`.
+    pub fn predict(&self, n: f64) -> f64 {
+        self.constant * self.model.growth(n)
+    }
+}
+
+/// Returns `count` sizes evenly spread up to `max`, starting from the smallest step.
+pub fn sizes(max: usize, count: usize) -> Vec<usize> {
+    let count = count.clamp(1, max.max(1));
+    (1..=count).map(|i| i * max / count).collect()
+}
+
+/// Sorts `size` numbers of the distribution with the algorithm tuned by `options`,
+/// counting the operations. The numbers are generated from `seed`,
+/// which also seeds randomized algorithms.
+pub fn sample(
+    algorithm: Algorithms,
+    options: &Options,
+    size: usize,
+    distribution: Distribution,
+    seed: u64,
+) -> Sample {
+    let mut sorter = algorithm.sorter_with(options);
+    sorter.seed(seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+    // The numbers don't matter much, as long as there's room for them to be distinct
+    let mut numbers = distribution.generate(0, size.max(1) * 4, size, &mut rng);
+    Sample {
+        size,
+        distribution,
+        stats: sorter.run(&mut numbers),
+    }
+}
+
+/// Takes a [`sample`] of every size and distribution, the sizes of each distribution in turn.
+pub fn sweep(
+    algorithm: Algorithms,
+    options: &Options,
+    sizes: &[usize],
+    distributions: &[Distribution],
+    seed: u64,
+) -> Vec<Sample> {
+    distributions
+        .iter()
+        .flat_map(|&distribution| {
+            sizes
+                .iter()
+                .map(move |&size| sample(algorithm, options, size, distribution, seed))
+        })
+        .collect()
+}
+
+/// Fits the counts of `metric` in `samples` to `model` with least squares.
+/// Returns `None` if there are no samples.
+pub fn fit(samples: &[Sample], metric: Metric, model: Model) -> Option<Fit> {
+    if samples.is_empty() {
+        return None;
+    }
+    let points: Vec<(f64, f64)> = samples
+        .iter()
+        .map(|sample| {
+            let growth = model.growth(sample.size as f64);
+            (growth, metric.count(&sample.stats) as f64)
+        })
+        .collect();
+
+    // Minimizing the sum of (count - constant * growth)² gives the constant
+    let squared_growth: f64 = points.iter().map(|(growth, _)| growth * growth).sum();
+    let constant = if squared_growth > 0. {
+        points
+            .iter()
+            .map(|(growth, count)| growth * count)
+            .sum::<f64>()
+            / squared_growth
+    } else {
+        0.
+    };
+
+    let n = points.len() as f64;
+    let mean = points.iter().map(|(_, count)| count).sum::<f64>() / n;
+    let squared_error: f64 = points
+        .iter()
+        .map(|(growth, count)| (count - constant * growth).powi(2))
+        .sum();
+    let error = if mean > 0. {
+        (squared_error / n).sqrt() / mean
+    } else {
+        0.
+    };
+
+    Some(Fit {
+        model,
+        constant,
+        error,
+    })
+}
+
+/// Fits the counts of `metric` in `samples` to every model, returning the closest one.
+pub fn best_fit(samples: &[Sample], metric: Metric) -> Option<Fit> {
+    Model::iter()
+        .filter_map(|model| fit(samples, metric, model))
+        .min_by(|a, b| a.error.total_cmp(&b.error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{best_fit, fit, sizes, sweep, Metric, Model, Sample};
+    use crate::algorithms::{options::Options, shell_sort::GapSequence, stats::Stats, Algorithms};
+    use crate::generator::Distribution;
+
+    fn samples(count: impl Fn(usize) -> usize) -> Vec<Sample> {
+        sizes(200, 8)
+            .into_iter()
+            .map(|size| Sample {
+                size,
+                distribution: Distribution::Random,
+                stats: Stats {
+                    comparisons: count(size),
+                    ..Default::default()
+                },
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fit_exact() {
+        let fit = fit(
+            &samples(|n| 3 * n * n),
+            Metric::Comparisons,
+            Model::Quadratic,
+        )
+        .unwrap();
+        assert!((fit.constant - 3.).abs() < 1e-9);
+        assert!(fit.error < 1e-9);
+        assert!((fit.predict(10.) - 300.).abs() < 1e-6);
+
+        let linear = best_fit(&samples(|n| 5 * n), Metric::Comparisons).unwrap();
+        assert_eq!(linear.model, Model::Linear);
+        assert_eq!(best_fit(&[], Metric::Comparisons), None);
+    }
+
+    #[test]
+    fn sweep_models() {
+        let sizes = sizes(128, 8);
+        assert_eq!(sizes, [16, 32, 48, 64, 80, 96, 112, 128]);
+
+        let distributions = [Distribution::Random];
+        let options = Options::default();
+        let bubble = sweep(Algorithms::Bubble, &options, &sizes, &distributions, 0);
+        assert_eq!(bubble.len(), sizes.len());
+        let fit = best_fit(&bubble, Metric::Comparisons).unwrap();
+        assert_eq!(fit.model, Model::Quadratic);
+
+        let merge = sweep(Algorithms::Merge, &options, &sizes, &distributions, 0);
+        let fit = best_fit(&merge, Metric::Comparisons).unwrap();
+        assert_eq!(fit.model, Model::Linearithmic);
+
+        // The options tune the algorithm swept
+        let halving = Options {
+            gaps: GapSequence::Shell,
+            ..options
+        };
+        let ciura = sweep(Algorithms::Shell, &options, &sizes, &distributions, 0);
+        let shell = sweep(Algorithms::Shell, &halving, &sizes, &distributions, 0);
+        assert_ne!(ciura, shell);
+    }
+}
@@ -0,0 +1,381 @@
+//! Runs an algorithm without the graphical interface, printing the result of the run.
+//! Useful for scripting experiments or checking the algorithms where there's no display.
+
+use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
+use sorting_visualizer::algorithms::radix_sort::{MAX_BASE, MIN_BASE};
+use sorting_visualizer::generator::{Swaps, MAX_SIZE, MAX_VALUE};
+use sorting_visualizer::{Algorithms, Distribution, Operation, Options as SorterOptions, Stats};
+use std::{env, fmt::Display, process::ExitCode, str::FromStr};
+
+const USAGE: &str = "\
+Usage: sorting-visualizer-cli <ALGORITHM> [OPTIONS]
+
+Algorithms: bubble, selection, insertion, merge, bogo, quick, heap, shell, lsdradix, msdradix,
+            counting, bucket, tim, intro, pdq, topdownmerge, naturalmerge, inplacemerge,
+            bitonic, oddevenmerge
+
+Options:
+  --size <N>            How many numbers to sort, at most 2000 [default: 20]
+  --floor <N>           Lowest number, inclusive [default: 1]
+  --ceil <N>            Highest number, exclusive, at most 10001 [default: 21]
+  --distribution <NAME> Shape of the numbers, e.g. random, sorted, reversed [default: random]
+  --swaps <N>           Random swaps of the nearlysorted distribution [default: 5]
+  --seed <N>            Seed of the numbers and of randomized algorithms [default: random]
+  --gaps <NAME>         Gaps of shell sort: shell, knuth, sedgewick, ciura, tokuda [default: ciura]
+  --base <N>            Base of the radix sorts [default: 10]
+  --partition <NAME>    Partition scheme of quick sort: lomuto, hoare, threeway, dualpivot
+                        [default: hoare]
+  --pivot <NAME>        Pivots of quick sort: first, last, random, medianofthree, ninther
+                        [default: medianofthree]
+  --max-steps <N>       Give up after this many steps [default: no limit]
+  --format <FORMAT>     Either text or json [default: text]
+  --trace               Also print the operations of every step
+  --help                Print this message";
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+enum Format {
+    Text,
+    Json,
+}
+
+/// What to run and how to print it, as given in the command line.
+#[derive(PartialEq, Debug)]
+struct Options {
+    algorithm: Algorithms,
+    size: usize,
+    floor: usize,
+    ceil: usize,
+    distribution: Distribution,
+    /// How the algorithm is tuned.
+    sorter: SorterOptions,
+    seed: u64,
+    max_steps: Option<usize>,
+    format: Format,
+    trace: bool,
+}
+
+/// Everything that happened during a run.
+struct Run {
+    input: Vec<usize>,
+    output: Vec<usize>,
+    /// The operations of every step, only kept when tracing.
+    trace: Vec<Vec<Operation>>,
+    stats: Stats,
+    finished: bool,
+}
+
+/// Parses the command line arguments, without the program name.
+/// Returns `Ok(None)` if the usage was asked for.
+fn parse(args: impl IntoIterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut args = args.into_iter();
+    let mut algorithm = None;
+    let mut swaps = None;
+    let mut options = Options {
+        algorithm: Algorithms::Bubble,
+        size: 20,
+        floor: 1,
+        ceil: 21,
+        distribution: Distribution::Random,
+        sorter: SorterOptions::default(),
+        seed: thread_rng().gen::<u32>().into(),
+        max_steps: None,
+        format: Format::Text,
+        trace: false,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {arg}"));
+        match arg.as_str() {
+            "--help" | "-h" => return Ok(None),
+            "--size" => options.size = parse_value(&arg, &value()?)?,
+            "--floor" => options.floor = parse_value(&arg, &value()?)?,
+            "--ceil" => options.ceil = parse_value(&arg, &value()?)?,
+            "--distribution" => options.distribution = parse_value(&arg, &value()?)?,
+            "--swaps" => swaps = Some(parse_value(&arg, &value()?)?),
+            "--gaps" => options.sorter.gaps = parse_value(&arg, &value()?)?,
+            "--base" => options.sorter.base = parse_value(&arg, &value()?)?,
+            "--partition" => options.sorter.partition = parse_value(&arg, &value()?)?,
+            "--pivot" => options.sorter.pivot = parse_value(&arg, &value()?)?,
+            "--seed" => options.seed = parse_value(&arg, &value()?)?,
+            "--max-steps" => options.max_steps = Some(parse_value(&arg, &value()?)?),
+            "--format" => {
+                options.format = match value()?.as_str() {
+                    "text" => Format::Text,
+                    "json" => Format::Json,
+                    other => return Err(format!("invalid value for --format: {other}")),
+                }
+            }
+            "--trace" => options.trace = true,
+            _ if arg.starts_with('-') => return Err(format!("unknown option {arg}")),
+            _ if algorithm.is_none() => algorithm = Some(parse_value("algorithm", &arg)?),
+            _ => return Err(format!("unexpected argument {arg}")),
+        }
+    }
+
+    options.algorithm = algorithm.ok_or("missing algorithm")?;
+    if !(MIN_BASE..=MAX_BASE).contains(&options.sorter.base) {
+        return Err(format!("--base must be from {MIN_BASE} to {MAX_BASE}"));
+    }
+    if options.size > MAX_SIZE {
+        return Err(format!("--size must be at most {MAX_SIZE}"));
+    }
+    if options.ceil > MAX_VALUE + 1 {
+        return Err(format!("--ceil must be at most {}", MAX_VALUE + 1));
+    }
+    if options.floor >= options.ceil {
+        return Err("--floor must be lower than --ceil".to_string());
+    }
+    if let Some(swaps) = swaps {
+        let Distribution::NearlySorted(Swaps(nearly)) = &mut options.distribution else {
+            return Err("--swaps only applies to the nearlysorted distribution".to_string());
+        };
+        if swaps > MAX_SIZE {
+            return Err(format!("--swaps must be at most {MAX_SIZE}"));
+        }
+        *nearly = swaps;
+    }
+    Ok(Some(options))
+}
+
+fn parse_value<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value for {name}: {value}"))
+}
+
+/// Generates the numbers and sorts them, one step at a time.
+fn execute(options: &Options) -> Run {
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    let input = options
+        .distribution
+        .generate(options.floor, options.ceil, options.size, &mut rng);
+    let mut sorter = options.algorithm.sorter_with(&options.sorter);
+    sorter.seed(options.seed);
+
+    let mut numbers = input.clone();
+    let mut trace = vec![];
+    let mut stats = Stats::default();
+    let mut finished = false;
+    while !finished && options.max_steps.map_or(true, |max| stats.steps < max) {
+        finished = sorter.step(&mut numbers);
+        stats.track(&*sorter);
+        if options.trace {
+            trace.push(sorter.operations().to_vec());
+        }
+    }
+
+    Run {
+        input,
+        output: numbers,
+        trace,
+        stats,
+        finished,
+    }
+}
+
+fn join(values: impl IntoIterator<Item = impl Display>, separator: &str) -> String {
+    values
+        .into_iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn text(options: &Options, run: &Run) -> String {
+    let mut lines = vec![
+        format!("algorithm: {:?}", options.algorithm),
+        format!("distribution: {}", options.distribution),
+        format!("seed: {}", options.seed),
+        format!("input: {}", join(&run.input, " ")),
+    ];
+    for (step, operations) in run.trace.iter().enumerate() {
+        let operations = operations.iter().map(|operation| format!("{operation:?}"));
+        let line = format!("step {}: {}", step + 1, join(operations, ", "));
+        lines.push(line.trim_end().to_string());
+    }
+    let result = if run.finished { "sorted" } else { "unfinished" };
+    lines.extend([
+        format!("{result}: {}", join(&run.output, " ")),
+        format!("steps: {}", run.stats.steps),
+        format!("comparisons: {}", run.stats.comparisons),
+        format!("swaps: {}", run.stats.swaps),
+        format!("writes: {}", run.stats.writes),
+        format!("aux writes: {}", run.stats.aux_writes),
+        format!("aux memory: {}", run.stats.aux_memory),
+    ]);
+    join(lines, "\n")
+}
+
+fn operation_json(operation: &Operation) -> String {
+    match *operation {
+        Operation::Compare(a, b) => format!(r#"{{"op":"compare","indexes":[{a},{b}]}}"#),
+        Operation::Swap(a, b) => format!(r#"{{"op":"swap","indexes":[{a},{b}]}}"#),
+        Operation::Read(i) => format!(r#"{{"op":"read","index":{i}}}"#),
+        Operation::Write(i, value) => {
+            format!(r#"{{"op":"write","index":{i},"value":{value}}}"#)
+        }
+        Operation::AuxWrite(i, value) => {
+            format!(r#"{{"op":"aux_write","index":{i},"value":{value}}}"#)
+        }
+        Operation::MarkPivot(i) => format!(r#"{{"op":"pivot","index":{i}}}"#),
+        Operation::MarkBoundary(i) => format!(r#"{{"op":"boundary","index":{i}}}"#),
+        Operation::EnterRange(lo, hi) => format!(r#"{{"op":"range","range":[{lo},{hi}]}}"#),
+    }
+}
+
+fn json(options: &Options, run: &Run) -> String {
+    let stats = &run.stats;
+    let mut fields = vec![
+        format!(r#""algorithm":"{:?}""#, options.algorithm),
+        format!(r#""distribution":"{}""#, options.distribution),
+        format!(r#""seed":{}"#, options.seed),
+        format!(r#""input":[{}]"#, join(&run.input, ",")),
+        format!(r#""output":[{}]"#, join(&run.output, ",")),
+        format!(r#""finished":{}"#, run.finished),
+        format!(
+            concat!(
+                r#""stats":{{"steps":{},"comparisons":{},"swaps":{},"writes":{},"#,
+                r#""aux_writes":{},"aux_memory":{}}}"#
+            ),
+            stats.steps,
+            stats.comparisons,
+            stats.swaps,
+            stats.writes,
+            stats.aux_writes,
+            stats.aux_memory
+        ),
+    ];
+    if options.trace {
+        let steps = run
+            .trace
+            .iter()
+            .map(|operations| format!("[{}]", join(operations.iter().map(operation_json), ",")));
+        fields.push(format!(r#""trace":[{}]"#, join(steps, ",")));
+    }
+    format!("{{{}}}", join(fields, ","))
+}
+
+fn main() -> ExitCode {
+    let options = match parse(env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(error) => {
+            eprintln!("error: {error}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let run = execute(&options);
+    match options.format {
+        Format::Text => println!("{}", text(&options, &run)),
+        Format::Json => println!("{}", json(&options, &run)),
+    }
+
+    if run.finished {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{execute, json, parse, Format, Options, SorterOptions, Swaps};
+    use sorting_visualizer::{
+        algorithms::{
+            quick_sort::{PartitionScheme, PivotStrategy},
+            shell_sort::GapSequence,
+        },
+        Algorithms, Distribution,
+    };
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn parse_options() {
+        let options = parse(args(
+            "Shell --size 5 --distribution organpipe --gaps knuth --seed 7 --format json --trace",
+        ))
+        .unwrap()
+        .unwrap();
+        let expected = Options {
+            algorithm: Algorithms::Shell,
+            size: 5,
+            floor: 1,
+            ceil: 21,
+            distribution: Distribution::OrganPipe,
+            sorter: SorterOptions {
+                gaps: GapSequence::Knuth,
+                ..Default::default()
+            },
+            seed: 7,
+            max_steps: None,
+            format: Format::Json,
+            trace: true,
+        };
+        assert_eq!(options, expected);
+
+        let options = parse(args("quick --partition threeway --pivot Random"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(options.sorter.partition, PartitionScheme::ThreeWay);
+        assert_eq!(options.sorter.pivot, PivotStrategy::Random);
+
+        assert_eq!(parse(args("heap --help")), Ok(None));
+        assert!(parse(args("")).is_err());
+        assert!(parse(args("slow")).is_err());
+        assert!(parse(args("merge --size")).is_err());
+        assert!(parse(args("merge --floor 5 --ceil 5")).is_err());
+        assert!(parse(args("lsdradix --base 1")).is_err());
+        assert!(parse(args("quick --pivot middle")).is_err());
+        assert!(parse(args("merge --size 2001")).is_err());
+        assert!(parse(args("merge --ceil 10002")).is_err());
+        assert!(parse(args("bucket --ceil 18446744073709551615")).is_err());
+        assert!(parse(args("merge --swaps 3")).is_err());
+
+        let options = parse(args("merge --swaps 3 --distribution nearlysorted"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(options.distribution, Distribution::NearlySorted(Swaps(3)));
+    }
+
+    #[test]
+    fn execute_run() {
+        let mut options = parse(args("bogo --size 6 --seed 3 --trace"))
+            .unwrap()
+            .unwrap();
+        let run = execute(&options);
+        assert!(run.finished);
+        assert_eq!(run.trace.len(), run.stats.steps);
+        let mut expected = run.input.clone();
+        expected.sort();
+        assert_eq!(run.output, expected);
+
+        // The same seed takes the same steps
+        assert_eq!(execute(&options).trace, run.trace);
+
+        options.max_steps = Some(2);
+        let run = execute(&options);
+        assert!(!run.finished);
+        assert_eq!(run.stats.steps, 2);
+    }
+
+    #[test]
+    fn json_trace() {
+        let options = parse(args(
+            "bubble --size 2 --distribution sorted --seed 1 --trace",
+        ))
+        .unwrap()
+        .unwrap();
+        let run = execute(&options);
+        let output = json(&options, &run);
+        assert!(output.starts_with(r#"{"algorithm":"Bubble","distribution":"Sorted","seed":1,"#));
+        // Bubble sort takes one more step to notice it's done
+        assert!(output.ends_with(r#""trace":[[{"op":"compare","indexes":[0,1]}],[]]}"#));
+    }
+}
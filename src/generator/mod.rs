@@ -0,0 +1,223 @@
+use crate::util;
+use rand::{seq::SliceRandom, Rng};
+use std::f64::consts::PI;
+use strum_macros::{Display, EnumIter, EnumString};
+
+/// Most numbers that the interface and the command line generate.
+pub const MAX_SIZE: usize = 2000;
+/// Highest number that the interface and the command line generate.
+pub const MAX_VALUE: usize = 10_000;
+/// How many distinct numbers `FewUnique` uses.
+const FEW_UNIQUE: usize = 4;
+/// How many ramps `Sawtooth` has.
+const TEETH: usize = 4;
+/// How many random swaps `NearlySorted` takes by default.
+pub const DEFAULT_SWAPS: usize = 5;
+
+/// How many random swaps `NearlySorted` takes.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Swaps(pub usize);
+
+impl Default for Swaps {
+    fn default() -> Swaps {
+        Swaps(DEFAULT_SWAPS)
+    }
+}
+
+/// The shapes the generated numbers can take.
+/// Names are parsed ignoring case, e.g. `"organpipe"` is `Distribution::OrganPipe`,
+/// and are displayed without the parameters of the distribution.
+#[derive(PartialEq, Eq, Debug, Display, EnumIter, EnumString, Clone, Copy)]
+#[strum(ascii_case_insensitive)]
+pub enum Distribution {
+    /// Uniformly distributed random numbers.
+    Random,
+    /// Distinct numbers, evenly spread over the range, in random order.
+    Permutation,
+    /// Random numbers in ascending order.
+    Sorted,
+    /// Random numbers in descending order.
+    Reversed,
+    /// Sorted numbers with a few random swaps.
+    NearlySorted(Swaps),
+    /// Random numbers drawn from a small set of distinct numbers.
+    FewUnique,
+    /// A few ascending ramps one after another.
+    Sawtooth,
+    /// Ascending up to the middle and then descending.
+    OrganPipe,
+    /// Random numbers following a normal distribution centered on the range.
+    Gaussian,
+    /// The same number repeated.
+    Equal,
+}
+
+impl Distribution {
+    /// Generates `n` numbers from `floor` (inclusive) to `ceil` (exclusive) shaped like the distribution.
+    pub fn generate(&self, floor: usize, ceil: usize, n: usize, rng: &mut impl Rng) -> Vec<usize> {
+        let span = ceil - floor;
+        match self {
+            Distribution::Random => util::gen_random_vector(floor, ceil, n, rng),
+            Distribution::Permutation => {
+                let mut numbers = spread(floor, span, n);
+                numbers.shuffle(rng);
+                numbers
+            }
+            Distribution::Sorted => {
+                let mut numbers = util::gen_random_vector(floor, ceil, n, rng);
+                numbers.sort();
+                numbers
+            }
+            Distribution::Reversed => {
+                let mut numbers = util::gen_random_vector(floor, ceil, n, rng);
+                numbers.sort_by(|a, b| b.cmp(a));
+                numbers
+            }
+            Distribution::NearlySorted(Swaps(swaps)) => {
+                let mut numbers = util::gen_random_vector(floor, ceil, n, rng);
+                numbers.sort();
+                if n > 1 {
+                    for _ in 0..*swaps {
+                        numbers.swap(rng.gen_range(0..n), rng.gen_range(0..n));
+                    }
+                }
+                numbers
+            }
+            Distribution::FewUnique => {
+                let values = spread(floor, span, FEW_UNIQUE.min(span));
+                (0..n).map(|_| *values.choose(rng).unwrap()).collect()
+            }
+            Distribution::Sawtooth => {
+                let tooth = n.div_ceil(TEETH).max(1);
+                (0..n)
+                    .map(|i| floor + util::scale(span, i % tooth, tooth as u128))
+                    .collect()
+            }
+            Distribution::OrganPipe => {
+                let half = n.div_ceil(2).max(1);
+                (0..n)
+                    .map(|i| floor + util::scale(span, i.min(n - 1 - i), half as u128))
+                    .collect()
+            }
+            Distribution::Gaussian => {
+                let mean = floor as f64 + span as f64 / 2.;
+                let deviation = span as f64 / 6.;
+                (0..n)
+                    .map(|_| {
+                        // Box-Muller transform
+                        let (u, v): (f64, f64) = (1. - rng.gen::<f64>(), rng.gen());
+                        let z = (-2. * u.ln()).sqrt() * (2. * PI * v).cos();
+                        let number = (mean + z * deviation).round() as usize;
+                        number.clamp(floor, ceil - 1)
+                    })
+                    .collect()
+            }
+            Distribution::Equal => vec![floor + span / 2; n],
+        }
+    }
+}
+
+/// Returns `n` ascending numbers evenly spread over the `span` starting at `floor`.
+/// The numbers are distinct as long as the span is at least `n`.
+fn spread(floor: usize, span: usize, n: usize) -> Vec<usize> {
+    (0..n)
+        .map(|i| floor + util::scale(span, i, n as u128))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Distribution, Swaps};
+    use rand::thread_rng;
+    use strum::IntoEnumIterator;
+
+    const FLOOR: usize = 10;
+    const CEIL: usize = 60;
+    const SIZE: usize = 40;
+
+    fn generate(distribution: Distribution) -> Vec<usize> {
+        distribution.generate(FLOOR, CEIL, SIZE, &mut thread_rng())
+    }
+
+    #[test]
+    fn bounds() {
+        for distribution in Distribution::iter() {
+            for size in [0, 1, 2, SIZE] {
+                let numbers = distribution.generate(FLOOR, CEIL, size, &mut thread_rng());
+                assert_eq!(numbers.len(), size);
+                assert!(numbers.iter().all(|n| (FLOOR..CEIL).contains(n)));
+            }
+        }
+    }
+
+    #[test]
+    fn shapes() {
+        assert!(generate(Distribution::Sorted)
+            .windows(2)
+            .all(|w| w[0] <= w[1]));
+        assert!(generate(Distribution::Reversed)
+            .windows(2)
+            .all(|w| w[0] >= w[1]));
+
+        let mut permutation = generate(Distribution::Permutation);
+        permutation.sort();
+        permutation.dedup();
+        assert_eq!(permutation.len(), SIZE);
+
+        let mut few = generate(Distribution::FewUnique);
+        few.sort();
+        few.dedup();
+        assert!(few.len() <= 4);
+
+        let equal = generate(Distribution::Equal);
+        assert!(equal.iter().all(|&n| n == equal[0]));
+
+        let pipe = generate(Distribution::OrganPipe);
+        assert!(pipe[..SIZE / 2].windows(2).all(|w| w[0] <= w[1]));
+        assert!(pipe[SIZE / 2..].windows(2).all(|w| w[0] >= w[1]));
+
+        let saw = generate(Distribution::Sawtooth);
+        let drops = saw.windows(2).filter(|w| w[0] > w[1]).count();
+        assert_eq!(drops, 3);
+    }
+
+    #[test]
+    fn nearly_sorted() {
+        let sorted =
+            Distribution::NearlySorted(Swaps(0)).generate(FLOOR, CEIL, SIZE, &mut thread_rng());
+        assert!(sorted.windows(2).all(|w| w[0] <= w[1]));
+
+        // A single swap moves at most two numbers out of place
+        let mut numbers = generate(Distribution::NearlySorted(Swaps(1)));
+        let mut expected = numbers.clone();
+        expected.sort();
+        let moved = numbers
+            .iter()
+            .zip(&expected)
+            .filter(|(a, b)| a != b)
+            .count();
+        assert!(moved <= 2);
+        numbers.sort();
+        assert_eq!(numbers, expected);
+
+        assert_eq!(
+            "nearlysorted".parse(),
+            Ok(Distribution::NearlySorted(Swaps::default()))
+        );
+    }
+
+    #[test]
+    fn wide_range() {
+        let generate = |distribution: Distribution| {
+            distribution.generate(0, usize::MAX, SIZE, &mut thread_rng())
+        };
+        for distribution in Distribution::iter() {
+            assert_eq!(generate(distribution).len(), SIZE);
+        }
+        let mut permutation = generate(Distribution::Permutation);
+        permutation.sort();
+        permutation.dedup();
+        assert_eq!(permutation.len(), SIZE);
+        assert!(generate(Distribution::OrganPipe)[SIZE / 2] > usize::MAX / 2);
+    }
+}
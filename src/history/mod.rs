@@ -0,0 +1,160 @@
+use crate::algorithms::{stats::Stats, Sorter};
+
+/// How many steps apart two checkpoints are.
+/// Restoring a step replays at most this many steps from the closest checkpoint.
+const CHECKPOINT_INTERVAL: usize = 256;
+
+/// Everything needed to resume a run from a given step.
+#[derive(Clone)]
+pub struct Checkpoint {
+    /// The numbers being sorted.
+    pub numbers: Vec<usize>,
+    /// The algorithm sorting them.
+    pub sorter: Box<dyn Sorter>,
+    /// The operations counted up to this point.
+    pub stats: Stats,
+}
+
+/// Computes a run ahead of time and keeps checkpoints of it,
+/// so that the run can be taken to any of its steps without starting over.
+/// Only every `CHECKPOINT_INTERVAL` steps are stored, the ones in between are replayed.
+pub struct History {
+    checkpoints: Vec<Checkpoint>,
+    /// The furthest step computed so far.
+    frontier: Checkpoint,
+    /// Whether the frontier has reached the end of the run.
+    finished: bool,
+}
+
+impl History {
+    /// Starts the history of a run, where `sorter` is about to take its first step over `numbers`.
+    pub fn new(numbers: &[usize], sorter: &dyn Sorter) -> Self {
+        let start = Checkpoint {
+            numbers: numbers.to_vec(),
+            sorter: sorter.clone_box(),
+            stats: Stats::default(),
+        };
+        History {
+            checkpoints: vec![start.clone()],
+            frontier: start,
+            finished: false,
+        }
+    }
+
+    /// Computes at most `budget` steps past the frontier, saving checkpoints along the way.
+    /// Returns true if the end of the run is known.
+    pub fn extend(&mut self, budget: usize) -> bool {
+        for _ in 0..budget {
+            if self.finished {
+                break;
+            }
+            let frontier = &mut self.frontier;
+            self.finished = frontier.sorter.step(&mut frontier.numbers);
+            frontier.stats.track(&*frontier.sorter);
+            if frontier.stats.steps % CHECKPOINT_INTERVAL == 0 {
+                self.checkpoints.push(frontier.clone());
+            }
+        }
+        self.finished
+    }
+
+    /// Returns how many steps have been computed so far.
+    pub fn known(&self) -> usize {
+        self.frontier.stats.steps
+    }
+
+    /// Returns how many steps the whole run takes, if it's already known.
+    pub fn length(&self) -> Option<usize> {
+        self.finished.then_some(self.known())
+    }
+
+    /// Returns the state of the run right after `step` steps were taken,
+    /// computing the run up to that step if needed.
+    /// Steps past the end of the run restore the end of the run.
+    pub fn restore(&mut self, step: usize) -> Checkpoint {
+        if step > self.known() {
+            self.extend(step - self.known());
+        }
+        let step = step.min(self.known());
+        let mut checkpoint = self.checkpoints[step / CHECKPOINT_INTERVAL].clone();
+        while checkpoint.stats.steps < step {
+            checkpoint.sorter.step(&mut checkpoint.numbers);
+            checkpoint.stats.track(&*checkpoint.sorter);
+        }
+        checkpoint
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::History;
+    use crate::{
+        algorithms::{
+            bogo_sort::BogoSort, heap_sort::HeapSort, merge_sort::MergeSort, quick_sort::QuickSort,
+            stats::Stats, Operation, Sorter,
+        },
+        util,
+    };
+    use rand::thread_rng;
+
+    const FLOOR: usize = 1;
+    const CEIL: usize = 21;
+    const SIZE: usize = 20;
+
+    /// Runs `sorter` to completion, then restores every step and checks it against the original run.
+    fn rewind(mut sorter: impl Sorter, size: usize) {
+        let mut numbers = util::gen_random_vector(FLOOR, CEIL, size, &mut thread_rng());
+        let mut history = History::new(&numbers, &sorter);
+        let mut steps: Vec<(Vec<usize>, Vec<Operation>)> = vec![];
+        loop {
+            let done = sorter.step(&mut numbers);
+            steps.push((numbers.clone(), sorter.operations().to_vec()));
+            if done {
+                break;
+            }
+        }
+
+        for step in (1..=steps.len()).rev() {
+            let checkpoint = history.restore(step);
+            assert_eq!(checkpoint.stats.steps, step);
+            assert_eq!(checkpoint.numbers, steps[step - 1].0);
+            assert_eq!(checkpoint.sorter.operations(), steps[step - 1].1);
+        }
+    }
+
+    #[test]
+    fn restore() {
+        rewind(QuickSort::new(), SIZE);
+        rewind(MergeSort::new(), SIZE);
+        rewind(HeapSort::new(), SIZE);
+        rewind(BogoSort::new(), 4);
+    }
+
+    #[test]
+    fn restore_start() {
+        let numbers = vec![3, 2, 1];
+        let mut history = History::new(&numbers, &MergeSort::new());
+        let checkpoint = history.restore(0);
+        assert_eq!(checkpoint.numbers, numbers);
+        assert_eq!(checkpoint.stats, Stats::default());
+    }
+
+    #[test]
+    fn extend() {
+        let mut numbers = util::gen_random_vector(FLOOR, CEIL, SIZE, &mut thread_rng());
+        let mut history = History::new(&numbers, &HeapSort::new());
+        let stats = HeapSort::new().run(&mut numbers);
+
+        assert!(!history.extend(1));
+        assert_eq!(history.known(), 1);
+        assert_eq!(history.length(), None);
+
+        while !history.extend(100) {}
+        assert_eq!(history.length(), Some(stats.steps));
+
+        // Restoring past the end restores the end
+        let checkpoint = history.restore(stats.steps + 10);
+        assert_eq!(checkpoint.numbers, numbers);
+        assert_eq!(checkpoint.stats, stats);
+    }
+}
@@ -0,0 +1,38 @@
+//! Sorting algorithms that can be run one step at a time.
+//!
+//! Every algorithm implements [`Sorter`], which takes a single step over an array of numbers
+//! and reports the [`Operation`]s performed by it, such as comparing or switching numbers.
+//! This makes it possible to visualize, count or rewind the execution of the algorithms.
+//!
+//! ```
+//! use sorting_visualizer::{algorithms::bubble_sort::BubbleSort, Operation, Sorter};
+//!
+//! let mut numbers = vec![3, 1, 2];
+//! let mut sorter = BubbleSort::new();
+//!
+//! // The first step compares the first two numbers
+//! sorter.step(&mut numbers);
+//! assert_eq!(sorter.operations(), [Operation::Compare(0, 1)]);
+//!
+//! let stats = sorter.run(&mut numbers);
+//! assert_eq!(numbers, vec![1, 2, 3]);
+//! assert!(stats.swaps > 0);
+//! ```
+//!
+//! The numbers themselves can be generated from a [`Distribution`].
+//! The graphical interface is only built with the `gui` feature, enabled by default.
+#![warn(missing_docs)]
+
+/// The sorting algorithms and the [`Sorter`] trait they implement.
+pub mod algorithms;
+/// Fitting the operations counted over many runs to complexity classes.
+pub mod analysis;
+/// Generation of numbers to be sorted.
+pub mod generator;
+/// Rewinding and seeking through the steps of a run.
+pub mod history;
+/// Helpers shared by the rest of the crate.
+pub mod util;
+
+pub use algorithms::{options::Options, stats::Stats, Algorithms, Operation, Reasons, Sorter};
+pub use generator::Distribution;
@@ -0,0 +1,306 @@
+use eframe::egui::{
+    self,
+    plot::{Legend, Line, Plot, PlotPoints, Points},
+    Button, ComboBox, DragValue, Grid, ProgressBar, Ui, Window,
+};
+use sorting_visualizer::algorithms::{bogo_sort::MAX_PRACTICAL_SIZE, options::Options, Algorithms};
+use sorting_visualizer::analysis::{self, best_fit, fit, Metric, Model, Sample};
+use sorting_visualizer::generator::Distribution;
+use std::{
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+};
+use strum::IntoEnumIterator;
+
+/// How many sizes are sorted in an analysis.
+const SWEEP_POINTS: usize = 8;
+/// Largest size sorted when the window opens.
+const DEFAULT_MAX_SIZE: usize = 256;
+const MIN_MAX_SIZE: usize = SWEEP_POINTS;
+const MAX_MAX_SIZE: usize = 2000;
+/// How many points the model curves are drawn with.
+const CURVE_POINTS: usize = 100;
+const PLOT_HEIGHT: f32 = 300.;
+const BEST_FIT_WIDTH: f32 = 3.;
+const PROGRESS_WIDTH: f32 = 300.;
+
+/// A sweep being sorted on another thread, which sends each sample as it's taken.
+struct Pending {
+    algorithm: Algorithms,
+    receiver: Receiver<Sample>,
+    samples: Vec<Sample>,
+    /// How many samples the sweep takes.
+    total: usize,
+}
+
+/// A window that sorts arrays of increasing size with an algorithm
+/// and charts how its operations grow against the common complexity classes.
+pub(crate) struct AnalysisWindow {
+    pub(crate) open: bool,
+    metric: Metric,
+    /// Which distribution of the sweep is charted.
+    distribution: Distribution,
+    max_size: usize,
+    /// The algorithm analysed and the samples of its last sweep.
+    result: Option<(Algorithms, Vec<Sample>)>,
+    /// The sweep under way, if any. The last result is shown until it's done.
+    pending: Option<Pending>,
+}
+
+impl Default for AnalysisWindow {
+    fn default() -> Self {
+        AnalysisWindow {
+            open: false,
+            metric: Metric::Comparisons,
+            distribution: Distribution::Random,
+            max_size: DEFAULT_MAX_SIZE,
+            result: None,
+            pending: None,
+        }
+    }
+}
+
+impl AnalysisWindow {
+    /// Starts sorting every distribution over a sweep of sizes with the algorithm tuned by
+    /// `options`, on another thread so the window keeps responding. Replaces the sweep under way.
+    fn analyse(&mut self, algorithm: Algorithms, options: &Options, seed: u64) {
+        let max_size = match algorithm {
+            Algorithms::Bogo => self.max_size.min(MAX_PRACTICAL_SIZE),
+            _ => self.max_size,
+        };
+        let sizes = analysis::sizes(max_size, SWEEP_POINTS);
+        let options = *options;
+        let (sender, receiver) = mpsc::channel();
+        self.pending = Some(Pending {
+            algorithm,
+            receiver,
+            samples: vec![],
+            total: sizes.len() * Distribution::iter().count(),
+        });
+        thread::spawn(move || {
+            for distribution in Distribution::iter() {
+                for &size in &sizes {
+                    let sample = analysis::sample(algorithm, &options, size, distribution, seed);
+                    // The sweep was cancelled or replaced, so no one is waiting for the rest
+                    if sender.send(sample).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+    }
+
+    /// Collects the samples taken since the last call, which become the result once all are in.
+    fn poll(&mut self) {
+        let Some(pending) = &mut self.pending else {
+            return;
+        };
+        loop {
+            match pending.receiver.try_recv() {
+                Ok(sample) => pending.samples.push(sample),
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => break,
+            }
+        }
+        if let Some(pending) = self.pending.take() {
+            self.result = Some((pending.algorithm, pending.samples));
+        }
+    }
+
+    /// Returns the samples of the charted distribution.
+    fn samples(&self) -> Vec<Sample> {
+        self.result
+            .iter()
+            .flat_map(|(_, samples)| samples)
+            .filter(|sample| sample.distribution == self.distribution)
+            .copied()
+            .collect()
+    }
+
+    /// Shows the window, if open, analysing `algorithm` tuned by `options`
+    /// with numbers generated from `seed`. Nothing is analysed without an algorithm.
+    pub(crate) fn show(
+        &mut self,
+        ctx: &egui::Context,
+        algorithm: Option<Algorithms>,
+        options: &Options,
+        seed: u64,
+    ) {
+        self.poll();
+        if self.pending.is_some() {
+            ctx.request_repaint();
+        }
+        let mut open = self.open;
+        Window::new("Analysis")
+            .open(&mut open)
+            .default_width(500.)
+            .show(ctx, |ui| {
+                self.handle_controls(ui, algorithm, options, seed);
+                self.draw_fits(ui);
+                self.draw_chart(ui);
+            });
+        self.open = open;
+    }
+
+    fn handle_controls(
+        &mut self,
+        ui: &mut Ui,
+        algorithm: Option<Algorithms>,
+        options: &Options,
+        seed: u64,
+    ) {
+        ui.horizontal(|ui| {
+            ui.label("Count:");
+            ComboBox::from_id_source("analysis_metric")
+                .selected_text(format!("{:?}", self.metric))
+                .show_ui(ui, |ui| {
+                    for option in Metric::iter() {
+                        ui.selectable_value(&mut self.metric, option, format!("{option:?}"));
+                    }
+                });
+            ui.label("Input:");
+            ComboBox::from_id_source("analysis_distribution")
+                .selected_text(self.distribution.to_string())
+                .show_ui(ui, |ui| {
+                    for option in Distribution::iter() {
+                        ui.selectable_value(&mut self.distribution, option, option.to_string());
+                    }
+                });
+        });
+        ui.horizontal(|ui| {
+            ui.label("Sizes up to");
+            ui.add(DragValue::new(&mut self.max_size).clamp_range(MIN_MAX_SIZE..=MAX_MAX_SIZE));
+            let Some(algorithm) = algorithm else {
+                ui.add_enabled(false, Button::new("Analyse"));
+                ui.label("Custom networks only sort as many numbers as they have wires.");
+                return;
+            };
+            let text = format!("Analyse {algorithm:?}Sort");
+            if ui
+                .add_enabled(self.pending.is_none(), Button::new(text))
+                .clicked()
+            {
+                self.analyse(algorithm, options, seed);
+            }
+        });
+        if let Some(pending) = &self.pending {
+            let progress = pending.samples.len() as f32 / pending.total as f32;
+            let text = format!("Sorting with {:?}Sort", pending.algorithm);
+            ui.horizontal(|ui| {
+                ui.add(
+                    ProgressBar::new(progress)
+                        .text(text)
+                        .desired_width(PROGRESS_WIDTH),
+                );
+                if ui.button("Cancel").clicked() {
+                    self.pending = None;
+                }
+            });
+        }
+    }
+
+    /// Lists how well each model fits, with the closest one first.
+    fn draw_fits(&self, ui: &mut Ui) {
+        let Some((algorithm, _)) = self.result else {
+            ui.label("Sort arrays of increasing size to see how the operations grow.");
+            return;
+        };
+        let samples = self.samples();
+        let mut fits: Vec<_> = Model::iter()
+            .filter_map(|model| fit(&samples, self.metric, model))
+            .collect();
+        fits.sort_by(|a, b| a.error.total_cmp(&b.error));
+        ui.label(format!(
+            "{:?} of {algorithm:?}Sort over {} numbers",
+            self.metric, self.distribution
+        ));
+        Grid::new("analysis_fits").striped(true).show(ui, |ui| {
+            ui.strong("Model");
+            ui.strong("Constant");
+            ui.strong("Error");
+            ui.end_row();
+            for (i, fit) in fits.iter().enumerate() {
+                let name = if i == 0 {
+                    format!("{} (best)", fit.model.name())
+                } else {
+                    fit.model.name().to_string()
+                };
+                ui.label(name);
+                ui.label(format!("{:.3}", fit.constant));
+                ui.label(format!("{:.1}%", fit.error * 100.));
+                ui.end_row();
+            }
+        });
+    }
+
+    /// Charts the counts against the curve of every model.
+    fn draw_chart(&self, ui: &mut Ui) {
+        let samples = self.samples();
+        let best = best_fit(&samples, self.metric).map(|fit| fit.model);
+        let max_size = samples.iter().map(|sample| sample.size).max().unwrap_or(0);
+        let counts: Vec<[f64; 2]> = samples
+            .iter()
+            .map(|sample| [sample.size as f64, self.metric.count(&sample.stats) as f64])
+            .collect();
+        Plot::new("analysis_chart")
+            .height(PLOT_HEIGHT)
+            .legend(Legend::default())
+            .show(ui, |plot_ui| {
+                for model in Model::iter() {
+                    let Some(fit) = fit(&samples, self.metric, model) else {
+                        continue;
+                    };
+                    let curve: Vec<[f64; 2]> = (0..=CURVE_POINTS)
+                        .map(|i| {
+                            let n = (i * max_size) as f64 / CURVE_POINTS as f64;
+                            [n, fit.predict(n)]
+                        })
+                        .collect();
+                    let mut line = Line::new(PlotPoints::new(curve)).name(model.name());
+                    if best == Some(model) {
+                        line = line.width(BEST_FIT_WIDTH);
+                    }
+                    plot_ui.line(line);
+                }
+                plot_ui.points(Points::new(counts).radius(4.).name("Counted"));
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AnalysisWindow, SWEEP_POINTS};
+    use sorting_visualizer::algorithms::{
+        bogo_sort::MAX_PRACTICAL_SIZE, options::Options, Algorithms,
+    };
+    use sorting_visualizer::generator::Distribution;
+    use std::{thread, time::Duration};
+    use strum::IntoEnumIterator;
+
+    /// Polls the sweep under way until it's done.
+    fn wait(window: &mut AnalysisWindow) {
+        while window.pending.is_some() {
+            window.poll();
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn analyse() {
+        let mut window = AnalysisWindow::default();
+        window.analyse(Algorithms::Insertion, &Options::default(), 0);
+        assert!(window.result.is_none());
+        wait(&mut window);
+        let (algorithm, samples) = window.result.as_ref().unwrap();
+        assert_eq!(*algorithm, Algorithms::Insertion);
+        assert_eq!(samples.len(), SWEEP_POINTS * Distribution::iter().count());
+        assert_eq!(window.samples().len(), SWEEP_POINTS);
+
+        window.analyse(Algorithms::Bogo, &Options::default(), 0);
+        wait(&mut window);
+        assert!(window
+            .samples()
+            .iter()
+            .all(|sample| sample.size <= MAX_PRACTICAL_SIZE));
+    }
+}
@@ -1,39 +1,72 @@
 use crate::ui;
+use rand::{rngs::StdRng, SeedableRng};
 use ui::*;
 
-use super::constants::{CEIL, FLOOR, VECTOR_SIZE};
-
 pub struct ButtonHandler;
 
 impl ButtonHandler {
-    /// If not finished, takes a single step within the selected algorithm
-    /// Else, resets the app state.
+    /// Takes a single step within the selected algorithm, counting its operations.
+    /// If already finished, resets the app state first.
     pub(crate) fn handle_step(app: &mut Visualizer) {
-        if app.state != State::Finished && app.sorter.step(&mut app.numbers) {
-            app.state = State::Finished;
-        }
         if app.state == State::Finished {
             app.reset();
         }
+        let done = app.sorter.step(&mut app.numbers);
+        app.stats.track(&*app.sorter);
+        if done {
+            app.state = State::Finished;
+        }
     }
 
-    /// Resets `app` state and sets `numbers` to their initial state.
+    /// Takes the app back to the step before the current one,
+    /// restoring both the numbers and the algorithm's state.
+    pub(crate) fn handle_step_back(app: &mut Visualizer) {
+        if app.stats.steps > 0 {
+            ButtonHandler::handle_seek(app, app.stats.steps - 1);
+        }
+    }
+
+    /// Takes the app to the given step of the current run,
+    /// restoring both the numbers and the algorithm's state.
+    pub(crate) fn handle_seek(app: &mut Visualizer, step: usize) {
+        let checkpoint = app.history.restore(step);
+        app.numbers = checkpoint.numbers;
+        app.sorter = checkpoint.sorter;
+        app.stats = checkpoint.stats;
+        app.state = if app.history.length() == Some(app.stats.steps) {
+            State::Finished
+        } else {
+            State::Start
+        };
+    }
+
+    /// Sets `numbers` to their initial state and resets `app` state.
     pub(crate) fn handle_reset(app: &mut Visualizer) {
-        app.reset();
         app.numbers = app.original_numbers.clone();
+        app.reset();
     }
 
-    /// Resets `app` state, generates new numbers and update the initial state.
+    /// Picks a new seed and generates new numbers from it.
     pub(crate) fn handle_shuffle(app: &mut Visualizer) {
-        app.reset();
-        app.numbers = util::gen_random_vector(FLOOR, CEIL, VECTOR_SIZE);
+        app.seed = new_seed();
+        ButtonHandler::handle_generate(app);
+    }
+
+    /// Generates new numbers from the current seed, updates the initial state and resets `app` state.
+    pub(crate) fn handle_generate(app: &mut Visualizer) {
+        let mut rng = StdRng::seed_from_u64(app.seed);
+        app.numbers = app
+            .distribution
+            .generate(app.floor, app.ceil, app.size, &mut rng);
         app.original_numbers = app.numbers.clone();
+        app.reset();
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::ui::{self, buttons::ButtonHandler};
+    use crate::ui::{self, buttons::ButtonHandler, Algorithms, State};
+    use sorting_visualizer::{algorithms::Operation, generator::Distribution};
 
     #[test]
     fn handle_reset() {
@@ -49,6 +82,56 @@ mod tests {
         assert_eq!(app.original_numbers, app.numbers);
     }
 
+    #[test]
+    fn handle_shuffle_size() {
+        let mut app = ui::Visualizer {
+            size: 0,
+            ..Default::default()
+        };
+        ButtonHandler::handle_shuffle(&mut app);
+        assert!(app.numbers.is_empty());
+        ButtonHandler::handle_step(&mut app);
+        assert_eq!(app.state, State::Finished);
+
+        app.size = 100;
+        app.floor = 5;
+        app.ceil = 6;
+        ButtonHandler::handle_shuffle(&mut app);
+        assert_eq!(app.numbers, vec![5; 100]);
+
+        app.ceil = 500;
+        app.distribution = Distribution::Reversed;
+        ButtonHandler::handle_shuffle(&mut app);
+        assert!(app.numbers.windows(2).all(|w| w[0] >= w[1]));
+    }
+
+    #[test]
+    fn handle_generate() {
+        let mut app = ui::Visualizer {
+            selected: Algorithms::Bogo,
+            size: 5,
+            ..Default::default()
+        };
+        app.switch_algorithm();
+        app.seed = 7;
+        ButtonHandler::handle_generate(&mut app);
+        let numbers = app.numbers.clone();
+        while app.state != State::Finished {
+            ButtonHandler::handle_step(&mut app);
+        }
+        let stats = app.stats;
+
+        // Same seed, same numbers and same steps, even after shuffling in between
+        ButtonHandler::handle_shuffle(&mut app);
+        app.seed = 7;
+        ButtonHandler::handle_generate(&mut app);
+        assert_eq!(app.numbers, numbers);
+        while app.state != State::Finished {
+            ButtonHandler::handle_step(&mut app);
+        }
+        assert_eq!(app.stats, stats);
+    }
+
     #[test]
     fn handle_step() {
         let numbers: Vec<usize> = vec![5, 2, 6];
@@ -59,24 +142,99 @@ mod tests {
 
         // This test only works the way it does because it's defaulting to BubbleSort
 
-        // The first time the function is run the 2 first elements are set to be compared
+        // The first time the function is run the 2 first elements are compared
         ButtonHandler::handle_step(&mut app);
-        assert_eq!(app.sorter.special(), (0, 1));
+        assert_eq!(app.sorter.operations(), [Operation::Compare(0, 1)]);
 
         // Since 5 > 2,
-        // The pair is marked for switching
+        // The pair is switched
         ButtonHandler::handle_step(&mut app);
-        assert_eq!(app.sorter.special(), (0, 1));
+        assert_eq!(app.sorter.operations(), [Operation::Swap(0, 1)]);
 
         // After stepping, the new order is [2, 5, 6]
         // Since 2 < 5 we will modify the state: (0, 1) -> (1,2)
-        // And we will be comparing 5 and 6 next round
+        // And we compare 5 and 6
         ButtonHandler::handle_step(&mut app);
-        assert_eq!(app.sorter.special(), (1, 2));
+        assert_eq!(app.sorter.operations(), [Operation::Compare(1, 2)]);
 
         // Since 5 < 6 we will modify the state: (1, 2) -> (0, 1)
-        // And we will be comparing 2 and 5 next round
+        // And we compare 2 and 5
+        ButtonHandler::handle_step(&mut app);
+        assert_eq!(app.sorter.operations(), [Operation::Compare(0, 1)]);
+
+        assert_eq!(app.stats.steps, 4);
+        assert_eq!(app.stats.comparisons, 3);
+        assert_eq!(app.stats.swaps, 1);
+    }
+
+    #[test]
+    fn handle_step_back() {
+        let mut app = ui::Visualizer {
+            numbers: vec![5, 2, 6],
+            ..Default::default()
+        };
+        app.reset();
+
+        ButtonHandler::handle_step(&mut app);
+        ButtonHandler::handle_step(&mut app);
+        assert_eq!(app.numbers, vec![2, 5, 6]);
+
+        // Going back undoes the switch, and the comparison is highlighted again
+        ButtonHandler::handle_step_back(&mut app);
+        assert_eq!(app.numbers, vec![5, 2, 6]);
+        assert_eq!(app.sorter.operations(), [Operation::Compare(0, 1)]);
+        assert_eq!(app.stats.steps, 1);
+
+        // Stepping forward again redoes the switch
+        ButtonHandler::handle_step(&mut app);
+        assert_eq!(app.numbers, vec![2, 5, 6]);
+        assert_eq!(app.sorter.operations(), [Operation::Swap(0, 1)]);
+
+        ButtonHandler::handle_step_back(&mut app);
+        ButtonHandler::handle_step_back(&mut app);
+        assert_eq!(app.stats.steps, 0);
+        assert!(app.sorter.operations().is_empty());
+    }
+
+    #[test]
+    fn handle_seek() {
+        let mut app = ui::Visualizer {
+            numbers: vec![3, 1, 2],
+            ..Default::default()
+        };
+        app.reset();
+
+        ButtonHandler::handle_seek(&mut app, 2);
+        assert_eq!(app.numbers, vec![1, 3, 2]);
+        assert_eq!(app.stats.steps, 2);
+        assert_eq!(app.state, State::Start);
+
+        // Seeking past the end goes to the end
+        ButtonHandler::handle_seek(&mut app, usize::MAX);
+        assert_eq!(app.numbers, vec![1, 2, 3]);
+        assert_eq!(app.state, State::Finished);
+        assert_eq!(app.history.length(), Some(app.stats.steps));
+
+        ButtonHandler::handle_seek(&mut app, 0);
+        assert_eq!(app.numbers, vec![3, 1, 2]);
+        assert_eq!(app.stats.steps, 0);
+    }
+
+    #[test]
+    fn handle_step_finished() {
+        let mut app = ui::Visualizer {
+            numbers: vec![2, 1],
+            ..Default::default()
+        };
+        while app.state != State::Finished {
+            ButtonHandler::handle_step(&mut app);
+        }
+        assert_eq!(app.numbers, vec![1, 2]);
+        // The totals are kept until the next step
+        assert_eq!(app.stats.swaps, 1);
+
         ButtonHandler::handle_step(&mut app);
-        assert_eq!(app.sorter.special(), (0, 1));
+        assert_eq!(app.state, State::Start);
+        assert_eq!(app.stats.steps, 1);
     }
 }
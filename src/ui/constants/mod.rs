@@ -1,3 +1,8 @@
+pub use sorting_visualizer::generator::{MAX_SIZE, MAX_VALUE};
+
+/// Default lowest number generated.
 pub const FLOOR: usize = 1;
+/// Default ceiling of the numbers generated, exclusive.
 pub const CEIL: usize = 21;
+/// Default amount of numbers generated.
 pub const VECTOR_SIZE: usize = 20;
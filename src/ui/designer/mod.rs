@@ -0,0 +1,340 @@
+use super::network::slots;
+use eframe::{
+    egui::{self, DragValue, ScrollArea, Sense, TextEdit, Ui, Window},
+    epaint::{pos2, vec2, Color32, FontId, Stroke},
+};
+use sorting_visualizer::algorithms::network_sort::{Network, MAX_CHECKED_WIRES};
+use std::{fs, str::FromStr};
+
+/// Most wires a network can be designed with, few enough to be checked right away,
+/// so every network designed is within [`MAX_CHECKED_WIRES`].
+const MAX_WIRES: usize = 16;
+const _: () = assert!(MAX_WIRES <= MAX_CHECKED_WIRES);
+const DEFAULT_WIRES: usize = 4;
+const WIRE_SPACING: f32 = 32.;
+const ROW_HEIGHT: f32 = 18.;
+/// The editor scrolls past this height.
+const EDITOR_HEIGHT: f32 = 320.;
+const TEXT_ROWS: usize = 6;
+const WIRE_COLOR: Color32 = Color32::from_gray(110);
+const PICKED_COLOR: Color32 = Color32::from_rgb(255, 180, 100);
+const COMPARATOR_COLOR: Color32 = Color32::from_gray(220);
+const COMPARATOR_WIDTH: f32 = 2.;
+const DOT_RADIUS: f32 = 4.;
+
+/// Returns the row each comparator of `network` is drawn in, along with its layer
+/// and its position in the layer. A layer takes as many rows as its comparators overlap.
+fn rows(network: &Network) -> Vec<(usize, usize, usize)> {
+    let mut rows = vec![];
+    let mut first = 0;
+    for (l, layer) in network.layers.iter().enumerate() {
+        let slots = slots(layer);
+        let count = slots.iter().max().map_or(0, |&max| max + 1);
+        rows.extend(
+            slots
+                .into_iter()
+                .enumerate()
+                .map(|(i, slot)| (l, i, first + slot)),
+        );
+        first += count;
+    }
+    rows
+}
+
+/// A window to design sorting networks by hand, check whether they sort every input,
+/// run them on the numbers, and share them as text.
+pub(crate) struct Designer {
+    pub(crate) open: bool,
+    network: Network,
+    /// The wire clicked first, waiting for the other end of a comparator.
+    picked: Option<usize>,
+    /// The network as text, to be copied or edited and loaded.
+    text: String,
+    /// Where the network is saved to and loaded from.
+    path: String,
+    /// What the last check, save or load came to.
+    message: String,
+}
+
+impl Default for Designer {
+    fn default() -> Self {
+        Designer {
+            open: false,
+            network: Network {
+                wires: DEFAULT_WIRES,
+                ..Default::default()
+            },
+            picked: None,
+            text: String::new(),
+            path: "network.txt".to_string(),
+            message: String::new(),
+        }
+    }
+}
+
+impl Designer {
+    /// Picks one end of a comparator, adding it once the other end is picked.
+    /// Picking the same wire twice drops it.
+    fn pick(&mut self, wire: usize) {
+        match self.picked.take() {
+            Some(first) => self.network.add(first, wire),
+            None => self.picked = Some(wire),
+        }
+    }
+
+    /// Runs the network over every input of zeros and ones, telling whether it sorts.
+    fn check(&mut self) {
+        self.message = match self.network.counterexample() {
+            Ok(None) => format!(
+                "Sorts all {} inputs of zeros and ones, so it sorts every input.",
+                1_u64 << self.network.wires
+            ),
+            Ok(Some(input)) => {
+                let input: Vec<String> = input.iter().map(usize::to_string).collect();
+                format!("Doesn't sort, {} is left unsorted.", input.join(" "))
+            }
+            Err(error) => format!("{error}."),
+        };
+    }
+
+    /// Replaces the network with the one written in `text`.
+    fn load(&mut self, text: &str) {
+        match Network::from_str(text) {
+            Ok(network) if network.wires > MAX_WIRES => {
+                self.message = format!("Networks can have at most {MAX_WIRES} wires.");
+            }
+            Ok(network) => {
+                self.network = network;
+                self.picked = None;
+                self.message = "Loaded.".to_string();
+            }
+            Err(error) => self.message = format!("Couldn't load the network: {error}."),
+        }
+    }
+
+    /// Shows the window, if open, for an array of `len` numbers.
+    /// Returns the network if it's to be run on the numbers.
+    pub(crate) fn show(&mut self, ctx: &egui::Context, len: usize) -> Option<Network> {
+        let mut open = self.open;
+        let mut run = false;
+        Window::new("Network designer")
+            .open(&mut open)
+            .default_width(400.)
+            .show(ctx, |ui| {
+                run = self.handle_controls(ui, len);
+                ui.label("Click two wires to join them, right-click a comparator to remove it.");
+                ScrollArea::vertical()
+                    .max_height(EDITOR_HEIGHT)
+                    .show(ui, |ui| self.draw_editor(ui));
+                self.handle_sharing(ui);
+                if !self.message.is_empty() {
+                    ui.label(&self.message);
+                }
+            });
+        self.open = open;
+        run.then(|| self.network.clone())
+    }
+
+    /// Creates the controls for the wires and the whole network.
+    /// Returns true if the network is to be run on the numbers.
+    fn handle_controls(&mut self, ui: &mut Ui, len: usize) -> bool {
+        let mut wires = self.network.wires;
+        ui.horizontal(|ui| {
+            ui.label("Wires:");
+            ui.add(DragValue::new(&mut wires).clamp_range(2..=MAX_WIRES));
+            if ui.button("As many as numbers").clicked() {
+                wires = len.clamp(2, MAX_WIRES);
+            }
+            if ui.button("Clear").clicked() {
+                self.network = Network {
+                    wires,
+                    ..Default::default()
+                };
+            }
+        });
+        if wires != self.network.wires {
+            self.network.resize(wires);
+            self.picked = None;
+        }
+        ui.horizontal(|ui| {
+            ui.label("Start from:");
+            if ui.button("Bitonic").clicked() {
+                self.network = Network::bitonic(wires);
+            }
+            if ui.button("Odd-even merge").clicked() {
+                self.network = Network::odd_even_merge(wires);
+            }
+        });
+        ui.label(format!(
+            "{} comparators in {} layers",
+            self.network.size(),
+            self.network.layers.len()
+        ));
+        let mut run = false;
+        ui.horizontal(|ui| {
+            if ui.button("Check").clicked() {
+                self.check();
+            }
+            run = ui.button("Run on the numbers").clicked();
+            if len != self.network.wires {
+                ui.label(format!("The array has {len} numbers"));
+            }
+        });
+        run
+    }
+
+    /// Draws the wires going down, with each layer of comparators beneath the one before,
+    /// and adds or removes comparators where clicked.
+    fn draw_editor(&mut self, ui: &mut Ui) {
+        let rows = rows(&self.network);
+        let count = rows.iter().map(|&(_, _, row)| row + 1).max().unwrap_or(0);
+        // A row for the wire numbers on top and an empty one at the bottom to click on
+        let size = vec2(
+            self.network.wires as f32 * WIRE_SPACING,
+            (count + 2) as f32 * ROW_HEIGHT,
+        );
+        let (rect, response) = ui.allocate_exact_size(size, Sense::click());
+        let x = |wire: usize| rect.left() + (wire as f32 + 0.5) * WIRE_SPACING;
+        let y = |row: usize| rect.top() + (row as f32 + 1.5) * ROW_HEIGHT;
+
+        let painter = ui.painter();
+        for wire in 0..self.network.wires {
+            let (color, width) = if self.picked == Some(wire) {
+                (PICKED_COLOR, 3.)
+            } else {
+                (WIRE_COLOR, 1.)
+            };
+            painter.text(
+                pos2(x(wire), rect.top()),
+                egui::Align2::CENTER_TOP,
+                wire.to_string(),
+                FontId::monospace(ROW_HEIGHT - 4.),
+                color,
+            );
+            painter.line_segment(
+                [
+                    pos2(x(wire), rect.top() + ROW_HEIGHT),
+                    pos2(x(wire), rect.bottom()),
+                ],
+                Stroke::new(width, color),
+            );
+        }
+        for &(l, i, row) in &rows {
+            let (a, b) = self.network.layers[l][i];
+            let stroke = Stroke::new(COMPARATOR_WIDTH, COMPARATOR_COLOR);
+            painter.line_segment([pos2(x(a), y(row)), pos2(x(b), y(row))], stroke);
+            painter.circle_filled(pos2(x(a), y(row)), DOT_RADIUS, COMPARATOR_COLOR);
+            painter.circle_filled(pos2(x(b), y(row)), DOT_RADIUS, COMPARATOR_COLOR);
+        }
+
+        let Some(pointer) = response.interact_pointer_pos() else {
+            return;
+        };
+        let wire = ((pointer.x - rect.left()) / WIRE_SPACING) as usize;
+        if response.clicked() && wire < self.network.wires {
+            self.pick(wire);
+        }
+        if response.secondary_clicked() {
+            let row = ((pointer.y - rect.top()) / ROW_HEIGHT - 1.).floor();
+            let hit = rows.iter().find(|&&(l, i, r)| {
+                let (a, b) = self.network.layers[l][i];
+                r as f32 == row && (a..=b).contains(&wire)
+            });
+            if let Some(&(l, i, _)) = hit {
+                self.network.remove(l, i);
+            }
+        }
+    }
+
+    /// Creates the controls to save the network as text, and to load it back.
+    fn handle_sharing(&mut self, ui: &mut Ui) {
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.button("To text").clicked() {
+                self.text = self.network.to_string();
+            }
+            if ui.button("From text").clicked() {
+                let text = self.text.clone();
+                self.load(&text);
+            }
+        });
+        ui.add(
+            TextEdit::multiline(&mut self.text)
+                .code_editor()
+                .desired_rows(TEXT_ROWS)
+                .desired_width(f32::INFINITY),
+        );
+        ui.horizontal(|ui| {
+            ui.label("File:");
+            ui.text_edit_singleline(&mut self.path);
+            if ui.button("Save").clicked() {
+                self.message = match fs::write(&self.path, format!("{}\n", self.network)) {
+                    Ok(()) => format!("Saved to {}.", self.path),
+                    Err(error) => format!("Couldn't save to {}: {error}.", self.path),
+                };
+            }
+            if ui.button("Load").clicked() {
+                match fs::read_to_string(&self.path) {
+                    Ok(text) => self.load(&text),
+                    Err(error) => {
+                        self.message = format!("Couldn't load {}: {error}.", self.path);
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rows, Designer, MAX_WIRES};
+    use sorting_visualizer::algorithms::network_sort::Network;
+
+    #[test]
+    fn pick() {
+        let mut designer = Designer::default();
+        designer.pick(2);
+        assert_eq!(designer.picked, Some(2));
+        designer.pick(0);
+        assert_eq!(designer.picked, None);
+        assert_eq!(designer.network.layers, [[(0, 2)]]);
+        // Picking a wire twice adds nothing
+        designer.pick(1);
+        designer.pick(1);
+        assert_eq!(designer.network.size(), 1);
+
+        designer.check();
+        assert_eq!(designer.message, "Doesn't sort, 1 0 0 0 is left unsorted.");
+        designer.network = Network::bitonic(4);
+        designer.check();
+        assert!(designer.message.starts_with("Sorts all 16 inputs"));
+    }
+
+    #[test]
+    fn load() {
+        let mut designer = Designer::default();
+        designer.load("wires 3\n0-1\n1-2\n0-1");
+        assert_eq!(designer.network.layers.len(), 3);
+        designer.load("wires 3\n0-3");
+        assert!(designer.message.starts_with("Couldn't load"));
+        designer.load(&format!("wires {}", MAX_WIRES + 1));
+        assert_eq!(designer.network.wires, 3);
+    }
+
+    #[test]
+    fn overlapping_rows() {
+        let network = Network::bitonic(4);
+        // The flipping layer takes two rows, as its comparators overlap
+        assert_eq!(
+            rows(&network),
+            [
+                (0, 0, 0),
+                (0, 1, 0),
+                (1, 0, 1),
+                (1, 1, 2),
+                (2, 0, 3),
+                (2, 1, 3)
+            ]
+        );
+    }
+}
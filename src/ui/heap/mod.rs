@@ -0,0 +1,99 @@
+use super::{PADDING, WRITING_COLOR};
+use eframe::{
+    egui::{self, Ui},
+    epaint::{pos2, Color32, FontId, Pos2, Rect, Stroke},
+};
+use sorting_visualizer::algorithms::Heap;
+
+/// Deeper levels are left out, as their nodes would be too small to tell apart.
+const MAX_LEVELS: u32 = 7;
+/// Nodes with a smaller radius are drawn without a label.
+const MIN_LABELED_RADIUS: f32 = 9.;
+const MAX_RADIUS: f32 = 18.;
+const EDGE_COLOR: Color32 = Color32::from_gray(110);
+const EDGE_WIDTH: f32 = 1.;
+const SIFTING_COLOR: Color32 = Color32::WHITE;
+const HIGHLIGHT_WIDTH: f32 = 3.;
+
+/// Returns the level of the node `offset` positions after the root, and its position in the level.
+fn place(offset: usize) -> (u32, usize) {
+    let level = (offset + 1).ilog2();
+    (level, offset + 1 - (1 << level))
+}
+
+/// Draws the numbers of `heap` as a binary tree filling `rect`, each node colored like its bar,
+/// with the number being sifted down and its children outlined.
+pub(super) fn draw_heap(ui: &Ui, rect: Rect, numbers: &[usize], colors: &[Color32], heap: Heap) {
+    let rect = rect.shrink(PADDING);
+    if heap.end >= numbers.len() || heap.start > heap.end {
+        return;
+    }
+    let size = heap.end - heap.start + 1;
+    let levels = (size.ilog2() + 1).min(MAX_LEVELS);
+    let level_height = rect.height() / levels as f32;
+    let center = |offset: usize| -> Pos2 {
+        let (level, position) = place(offset);
+        let width = rect.width() / (1 << level) as f32;
+        pos2(
+            rect.left() + (position as f32 + 0.5) * width,
+            rect.top() + (level as f32 + 0.5) * level_height,
+        )
+    };
+    let shown = size.min((1 << levels) - 1);
+    let deepest = rect.width() / (1 << (levels - 1)) as f32;
+    let radius = (deepest.min(level_height) * 0.4).min(MAX_RADIUS);
+
+    let painter = ui.painter();
+    for offset in 1..shown {
+        let parent = (offset - 1) / 2;
+        painter.line_segment(
+            [center(parent), center(offset)],
+            Stroke::new(EDGE_WIDTH, EDGE_COLOR),
+        );
+    }
+    let children = heap.sifting.map(|i| heap.children(i)).unwrap_or_default();
+    for offset in 0..shown {
+        let i = heap.start + offset;
+        let stroke = if heap.sifting == Some(i) {
+            Stroke::new(HIGHLIGHT_WIDTH, SIFTING_COLOR)
+        } else if children.contains(&i) {
+            Stroke::new(HIGHLIGHT_WIDTH, WRITING_COLOR)
+        } else {
+            Stroke::NONE
+        };
+        let color = colors.get(i).copied().unwrap_or(Color32::GRAY);
+        painter.circle(center(offset), radius, color, stroke);
+        if radius >= MIN_LABELED_RADIUS {
+            painter.text(
+                center(offset),
+                egui::Align2::CENTER_CENTER,
+                numbers[i].to_string(),
+                FontId::proportional(radius),
+                Color32::BLACK,
+            );
+        }
+    }
+    if shown < size {
+        painter.text(
+            rect.left_bottom(),
+            egui::Align2::LEFT_BOTTOM,
+            format!("{} deeper numbers not shown", size - shown),
+            FontId::default(),
+            ui.visuals().text_color(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::place;
+
+    #[test]
+    fn places() {
+        assert_eq!(place(0), (0, 0));
+        assert_eq!(place(1), (1, 0));
+        assert_eq!(place(2), (1, 1));
+        assert_eq!(place(6), (2, 3));
+        assert_eq!(place(7), (3, 0));
+    }
+}
@@ -1,42 +1,244 @@
+mod analysis;
 mod buttons;
 pub mod constants;
-use self::constants::{CEIL, FLOOR, VECTOR_SIZE};
-use crate::algorithms::{
-    bogo_sort::BogoSort, bubble_sort::BubbleSort, heap_sort::HeapSort,
-    insertion_sort::InsertionSort, merge_sort::MergeSort, quick_sort::QuickSort,
-    selection_sort::SelectionSort, Reasons, Sorter,
-};
-use crate::util;
+mod designer;
+mod heap;
+mod network;
+mod race;
+mod scheduler;
+mod stack;
+use self::constants::{CEIL, FLOOR, MAX_SIZE, MAX_VALUE, VECTOR_SIZE};
+use analysis::AnalysisWindow;
 use buttons::ButtonHandler;
+use designer::Designer;
 use eframe::{
-    egui::{self, Button, CentralPanel, ComboBox, Grid, Sense, Ui},
-    epaint::{vec2, Color32, Rect, Stroke, Vec2},
+    egui::{self, Button, CentralPanel, ComboBox, DragValue, Grid, Sense, SidePanel, Slider, Ui},
+    epaint::{pos2, Color32, FontId, Rect, Stroke},
+};
+use heap::draw_heap;
+use network::draw_network;
+use race::Race;
+use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
+use scheduler::{Scheduler, MAX_SPEED, MIN_SPEED};
+use sorting_visualizer::algorithms::{
+    network_sort::{Network, NetworkSort},
+    options::Options,
+    quick_sort::{PartitionScheme, PivotStrategy},
+    radix_sort::{MAX_BASE, MIN_BASE},
+    shell_sort::GapSequence,
+    stats::Stats,
+    Algorithms, Buffer, Operation, Reasons, Sorter,
+};
+use sorting_visualizer::{
+    generator::{Distribution, Swaps},
+    history::History,
+};
+use stack::draw_stack;
+use std::{
+    mem::discriminant,
+    time::{Duration, Instant},
 };
-use std::{thread, time::Duration};
 use strum::IntoEnumIterator;
-use strum_macros::EnumIter;
-
-#[derive(PartialEq, Debug, EnumIter, Clone, Copy)]
-enum Algorithms {
-    Bubble,
-    Selection,
-    Insertion,
-    Merge,
-    Bogo,
-    Quick,
-    Heap,
-}
 
 const CENTRALIZE_PADDING: f32 = 300.;
 const PADDING: f32 = 10.;
-const BASE_HEIGHT: usize = 32;
-const BASE_WIDTH: f32 = 16.;
 const ROUNDING: f32 = 5.;
 const STROKE_WIDTH: f32 = 2.;
-const NUMBERS_GRID: &str = "numbers";
+/// Fraction of a bar's slot left empty, so neighbouring bars don't touch.
+const BAR_GAP: f32 = 0.15;
+/// Bars narrower than this are drawn without a label.
+const MIN_LABELED_WIDTH: f32 = 14.;
+/// Bars narrower than this are drawn without a stroke.
+const MIN_STROKED_WIDTH: f32 = 6.;
+const LABEL_HEIGHT: f32 = 20.;
+const STATS_GRID: &str = "stats";
+const STATS_PANEL: &str = "stats_panel";
 const STROKE_COLOR: Color32 = Color32::WHITE;
-const WAIT_TIME: Duration = Duration::from_millis(120);
-const FLOOR_POS: f32 = 700.0;
+const WRITING_COLOR: Color32 = Color32::from_rgb(255, 180, 100);
+const BOUNDARY_COLOR: Color32 = Color32::from_rgb(190, 150, 255);
+const RANGE_COLOR: Color32 = Color32::from_rgb(120, 200, 255);
+const RANGE_WIDTH: f32 = 1.5;
+const CURSOR_COLOR: Color32 = Color32::from_rgb(255, 120, 120);
+const ACTIVE_RANGE_COLOR: Color32 = Color32::from_rgb(160, 110, 30);
+/// How opaque the shade over the active range of the bars is.
+const ACTIVE_RANGE_SHADE: f32 = 0.3;
+/// Fraction of the height given to the buffers of an algorithm, when it has any.
+const BUFFERS_HEIGHT: f32 = 0.4;
+const TIMELINE_WIDTH: f32 = 300.;
+/// How long each frame spends computing the run ahead, for the timeline.
+const PRECOMPUTE_TIME: Duration = Duration::from_millis(4);
+/// How many steps are computed ahead between checks of the time spent.
+const PRECOMPUTE_CHUNK: usize = 64;
+/// Runs are only computed ahead up to this step, so endless runs don't exhaust memory.
+const PRECOMPUTE_LIMIT: usize = 1_000_000;
+
+/// Returns a random seed, small enough to be shown and typed in exactly.
+fn new_seed() -> u64 {
+    thread_rng().gen::<u32>().into()
+}
+
+/// Draws rectangles representing the numbers, whose height is proportional to the number,
+/// with `highest` reaching the top. The rectangles are scaled to fill `rect`,
+/// and use the number as a centralized label if they are wide enough.
+fn draw_bars(ui: &Ui, rect: Rect, numbers: &[usize], colors: &[Color32], highest: usize) {
+    let rect = rect.shrink(PADDING);
+    if numbers.is_empty() {
+        return;
+    }
+    let highest = highest.max(1);
+    let width = rect.width() / numbers.len() as f32;
+    let labeled = width >= MIN_LABELED_WIDTH;
+    let top = if labeled {
+        rect.top() + LABEL_HEIGHT
+    } else {
+        rect.top()
+    };
+    let unit = (rect.bottom() - top) / highest as f32;
+    let stroke = if width >= MIN_STROKED_WIDTH {
+        Stroke::new(STROKE_WIDTH, STROKE_COLOR)
+    } else {
+        Stroke::NONE
+    };
+    let gap = width * BAR_GAP / 2.;
+
+    let painter = ui.painter();
+    for (i, (number, color)) in numbers.iter().zip(colors).enumerate() {
+        let left = rect.left() + i as f32 * width;
+        let bar = Rect::from_min_max(
+            pos2(left + gap, rect.bottom() - *number as f32 * unit),
+            pos2(left + width - gap, rect.bottom()),
+        );
+        painter.rect(bar, ROUNDING.min(width / 4.), *color, stroke);
+        if labeled {
+            painter.text(
+                pos2(bar.center().x, bar.top() - 2.),
+                egui::Align2::CENTER_BOTTOM,
+                number.to_string(),
+                FontId::default(),
+                ui.visuals().text_color(),
+            );
+        }
+    }
+}
+
+/// Draws the boundaries of `ranges` over bars drawn by [`draw_bars`] into `rect`,
+/// as lines before the first and after the last bar of each range, joined beneath the bars.
+fn draw_ranges(ui: &Ui, rect: Rect, len: usize, ranges: &[(usize, usize)]) {
+    let rect = rect.shrink(PADDING);
+    if len == 0 {
+        return;
+    }
+    let width = rect.width() / len as f32;
+    let stroke = Stroke::new(RANGE_WIDTH, RANGE_COLOR);
+    let bottom = rect.bottom() + PADDING / 2.;
+    let painter = ui.painter();
+    for &(lo, hi) in ranges {
+        let left = rect.left() + lo as f32 * width;
+        let right = rect.left() + (hi + 1) as f32 * width;
+        painter.line_segment([pos2(left, rect.top()), pos2(left, bottom)], stroke);
+        painter.line_segment([pos2(right, rect.top()), pos2(right, bottom)], stroke);
+        painter.line_segment([pos2(left, bottom), pos2(right, bottom)], stroke);
+    }
+}
+
+/// Shades the positions from `lo` to `hi` behind bars drawn by [`draw_bars`] into `rect`.
+fn draw_active_range(ui: &Ui, rect: Rect, len: usize, (lo, hi): (usize, usize)) {
+    let rect = rect.shrink(PADDING);
+    if len == 0 {
+        return;
+    }
+    let width = rect.width() / len as f32;
+    let shade = Rect::from_min_max(
+        pos2(rect.left() + lo as f32 * width, rect.top()),
+        pos2(rect.left() + (hi + 1) as f32 * width, rect.bottom()),
+    );
+    let color = ACTIVE_RANGE_COLOR.gamma_multiply(ACTIVE_RANGE_SHADE);
+    ui.painter().rect_filled(shade, 0., color);
+}
+
+/// Names the positions of `cursors` beneath bars drawn by [`draw_bars`] into `rect`,
+/// stacking the names of cursors that point at the same position.
+fn draw_cursors(ui: &Ui, rect: Rect, len: usize, cursors: &[(String, usize)]) {
+    let rect = rect.shrink(PADDING);
+    if len == 0 {
+        return;
+    }
+    let width = rect.width() / len as f32;
+    let painter = ui.painter();
+    for (i, (name, position)) in cursors.iter().enumerate() {
+        let before = cursors[..i].iter().filter(|(_, p)| p == position).count();
+        let center = rect.left() + (*position as f32 + 0.5) * width;
+        painter.text(
+            pos2(center, rect.bottom() + before as f32 * PADDING),
+            egui::Align2::CENTER_TOP,
+            name,
+            FontId::monospace(PADDING),
+            CURSOR_COLOR,
+        );
+    }
+}
+
+/// Draws the buffers side by side in `rect`, each with its label on top
+/// and the numbers written by the last step highlighted, along with its cursors.
+fn draw_buffers(ui: &Ui, rect: Rect, buffers: &[Buffer], highest: usize) {
+    let width = rect.width() / buffers.len().max(1) as f32;
+    for (i, buffer) in buffers.iter().enumerate() {
+        let left = rect.left() + i as f32 * width;
+        let tile = Rect::from_min_max(pos2(left, rect.top()), pos2(left + width, rect.bottom()));
+        ui.painter().rect_stroke(
+            tile.shrink(PADDING / 2.),
+            ROUNDING,
+            Stroke::new(1., Color32::DARK_GRAY),
+        );
+        ui.painter().text(
+            pos2(tile.center().x, tile.top() + PADDING),
+            egui::Align2::CENTER_TOP,
+            &buffer.label,
+            FontId::default(),
+            ui.visuals().text_color(),
+        );
+        let mut colors = vec![Color32::GRAY; buffer.numbers.len()];
+        for &position in &buffer.highlighted {
+            if let Some(color) = colors.get_mut(position) {
+                *color = WRITING_COLOR;
+            }
+        }
+        let bars = Rect::from_min_max(pos2(tile.left(), tile.top() + LABEL_HEIGHT), tile.max);
+        // Buffers may hold counts rather than numbers, which can go higher
+        let highest = buffer.numbers.iter().copied().fold(highest, usize::max);
+        draw_bars(ui, bars, &buffer.numbers, &colors, highest);
+        draw_cursors(ui, bars, buffer.numbers.len(), &buffer.cursors);
+    }
+}
+
+/// Returns the color of each of the `len` numbers, based on the operations of the last step.
+/// When an index is touched by many operations, the latest one wins.
+/// Once `finished`, every number is drawn the same.
+fn operation_colors(operations: &[Operation], len: usize, finished: bool) -> Vec<Color32> {
+    let mut colors = vec![Color32::GRAY; len];
+    if finished {
+        return colors;
+    }
+    for operation in operations {
+        let Some(reason) = operation.reason() else {
+            continue;
+        };
+        let color = match reason {
+            Reasons::Comparing => Color32::LIGHT_YELLOW,
+            Reasons::Switching => Color32::LIGHT_GREEN,
+            Reasons::Reading => Color32::LIGHT_BLUE,
+            Reasons::Writing => WRITING_COLOR,
+            Reasons::Pivot => Color32::LIGHT_RED,
+            Reasons::Boundary => BOUNDARY_COLOR,
+        };
+        for i in operation.indexes() {
+            if let Some(c) = colors.get_mut(i) {
+                *c = color;
+            }
+        }
+    }
+    colors
+}
 
 #[derive(PartialEq, Debug)]
 enum State {
@@ -47,21 +249,55 @@ enum State {
 
 pub(crate) struct Visualizer<'a> {
     selected: Algorithms,
+    /// How the selected algorithm is tuned.
+    options: Options,
+    /// The network from the designer being run instead of the selected algorithm, if any.
+    custom: Option<Network>,
     numbers: Vec<usize>,
     original_numbers: Vec<usize>,
+    size: usize,
+    floor: usize,
+    ceil: usize,
+    distribution: Distribution,
+    /// Seeds both the numbers generated and randomized algorithms.
+    seed: u64,
     state: State,
     sorter: Box<dyn Sorter + 'a>,
+    stats: Stats,
+    history: History,
+    scheduler: Scheduler,
+    analysis: AnalysisWindow,
+    designer: Designer,
+    /// Set while racing many algorithms instead of running a single one.
+    race: Option<Race>,
 }
 
 impl<'a> Default for Visualizer<'a> {
     fn default() -> Self {
-        let numbers: Vec<usize> = util::gen_random_vector(FLOOR, CEIL, VECTOR_SIZE);
+        let seed = new_seed();
+        let mut rng = StdRng::seed_from_u64(seed);
+        let numbers: Vec<usize> = Distribution::Random.generate(FLOOR, CEIL, VECTOR_SIZE, &mut rng);
+        let selected = Algorithms::Bubble;
+        let sorter = selected.sorter();
         Self {
-            selected: Algorithms::Bubble,
+            selected,
+            options: Options::default(),
+            custom: None,
             numbers: numbers.clone(),
             state: State::Start,
+            history: History::new(&numbers, sorter.as_ref()),
             original_numbers: numbers,
-            sorter: Box::new(BubbleSort::new()),
+            size: VECTOR_SIZE,
+            floor: FLOOR,
+            ceil: CEIL,
+            distribution: Distribution::Random,
+            seed,
+            sorter,
+            stats: Stats::default(),
+            scheduler: Scheduler::default(),
+            analysis: AnalysisWindow::default(),
+            designer: Designer::default(),
+            race: None,
         }
     }
 }
@@ -75,79 +311,197 @@ impl Visualizer<'_> {
         Self::default()
     }
 
-    /// Draws rectangles representing the numbers, whose height is proportional to the number.
-    /// Use the number as a centralized label.
+    /// Draws the numbers as bars, scaled to fill the remaining space,
+    /// with the buffers of the algorithm, its heap as a tree, or its sorting network beneath them.
     fn draw_numbers(&self, ui: &mut Ui) {
-        let special: (usize, usize) = self.sorter.special();
-        let reason: Reasons = self.sorter.reason();
-        ui.horizontal_top(|ui| {
-            ui.add_space(PADDING);
-            for i in 0..self.numbers.len() {
-                let text = self.numbers[i].to_string();
-                let height: f32 = (self.numbers[i] * BASE_HEIGHT) as f32;
-                let size = vec2(BASE_WIDTH, FLOOR_POS - height);
-                let color = if (i == special.0 || i == special.1) && self.state != State::Finished {
-                    match reason {
-                        Reasons::Comparing => Color32::LIGHT_YELLOW,
-                        Reasons::Switching => Color32::LIGHT_GREEN,
-                    }
-                } else {
-                    Color32::GRAY
-                };
-                Visualizer::draw_numbers_helper(text, size, color, ui);
+        let (rect, _) = ui.allocate_exact_size(ui.available_size(), Sense::hover());
+        // Scaling by the highest possible number keeps the heights still while sorting
+        let highest = self.numbers.iter().copied().max().unwrap_or(0);
+        let highest = highest.max(self.ceil - 1);
+        let colors = self.colors();
+        // Ranges and heaps only make sense while sorting
+        let finished = self.state == State::Finished;
+        let buffers = self.sorter.buffers();
+        let heap = self.sorter.heap().filter(|_| !finished);
+        // Unlike the heap, the network is still worth a look once it's sorted
+        let network = self.sorter.network();
+        let top = if buffers.is_empty() && heap.is_none() && network.is_none() {
+            rect
+        } else {
+            let split = rect.bottom() - rect.height() * BUFFERS_HEIGHT;
+            let bottom = Rect::from_min_max(pos2(rect.left(), split), rect.max);
+            match (heap, network) {
+                _ if !buffers.is_empty() => draw_buffers(ui, bottom, &buffers, highest),
+                (Some(heap), _) => draw_heap(ui, bottom, &self.numbers, &colors, heap),
+                (None, Some((network, applied))) => {
+                    let operations = self.sorter.operations();
+                    draw_network(ui, bottom, self.numbers.len(), network, applied, operations);
+                }
+                (None, None) => {}
             }
-            ui.add_space(PADDING);
-        });
+            Rect::from_min_max(rect.min, pos2(rect.right(), split))
+        };
+        let len = self.numbers.len();
+        if let Some(active) = self.sorter.active_range().filter(|_| !finished) {
+            draw_active_range(ui, top, len, active);
+        }
+        draw_bars(ui, top, &self.numbers, &colors, highest);
+        if !finished {
+            draw_ranges(ui, top, len, &self.sorter.ranges());
+            draw_cursors(ui, top, len, &self.sorter.cursors());
+        }
     }
 
-    fn draw_numbers_helper(text: String, size: Vec2, color: Color32, ui: &mut Ui) {
-        Grid::new(NUMBERS_GRID).show(ui, |ui| {
-            ui.vertical_centered(|ui| {
-                let mut rect = ui.allocate_exact_size(size, Sense::hover()).0;
-                rect.set_top(size.y);
-                rect.set_bottom(FLOOR_POS);
-                let mut number_text: Rect = Rect::NOTHING;
-                number_text.extend_with(egui::pos2(rect.min.x, rect.min.y - 20.0));
-                number_text.extend_with(egui::pos2(rect.max.x, rect.min.y - 10.0));
-                ui.put(number_text, egui::Label::new(text));
-                ui.painter().rect(
-                    rect,
-                    ROUNDING,
-                    color,
-                    Stroke::new(STROKE_WIDTH, STROKE_COLOR),
-                );
-            });
+    /// Returns the color of each number, based on the operations of the last step.
+    fn colors(&self) -> Vec<Color32> {
+        operation_colors(
+            self.sorter.operations(),
+            self.numbers.len(),
+            self.state == State::Finished,
+        )
+    }
+
+    /// Draws the operations counted in the current run.
+    fn draw_stats(&self, ui: &mut Ui) {
+        ui.heading("Statistics");
+        ui.add_space(PADDING);
+        Grid::new(STATS_GRID).num_columns(2).show(ui, |ui| {
+            let rows = [
+                ("Steps", self.stats.steps),
+                ("Comparisons", self.stats.comparisons),
+                ("Swaps", self.stats.swaps),
+                ("Writes", self.stats.writes),
+                ("Aux. writes", self.stats.aux_writes),
+                ("Aux. memory", self.stats.aux_memory),
+            ];
+            for (label, value) in rows {
+                ui.label(label);
+                ui.label(value.to_string());
+                ui.end_row();
+            }
         });
+        if let Some(status) = self.sorter.status() {
+            ui.add_space(PADDING);
+            ui.label(status);
+        }
+        if self.state == State::Finished {
+            ui.add_space(PADDING);
+            ui.label("Finished, these are the final totals.");
+            return;
+        }
+        let ranges = self.sorter.ranges();
+        if !ranges.is_empty() {
+            ui.add_space(PADDING);
+            ui.heading("Ranges");
+            ui.label("The latest on top, the active one shaded.");
+            ui.add_space(PADDING);
+            draw_stack(ui, self.numbers.len(), &ranges, self.sorter.active_range());
+        }
     }
 
     /// Create the ComboBox and return true if algorithm selection has been changed.
+    /// Selecting any algorithm, even the one selected before, stops running the custom network.
     fn handle_combo_box(&mut self, ui: &mut Ui) -> bool {
-        let previous_selection: Algorithms = self.selected;
+        let mut changed = false;
         ui.label("Algorithm:");
+        let text = match self.custom {
+            Some(_) => "Custom network".to_string(),
+            None => format!("{:?}Sort", self.selected),
+        };
         ComboBox::from_id_source(0)
-            .selected_text(format!("{:?}Sort", self.selected))
+            .selected_text(text)
             .show_ui(ui, |ui| {
                 for option in Algorithms::iter() {
-                    ui.selectable_value(&mut self.selected, option, format!("{option:?}Sort"));
+                    let checked = self.custom.is_none() && self.selected == option;
+                    if ui
+                        .selectable_label(checked, format!("{option:?}Sort"))
+                        .clicked()
+                        && !checked
+                    {
+                        self.selected = option;
+                        self.custom = None;
+                        changed = true;
+                    }
                 }
             });
-        previous_selection != self.selected
+        changed
     }
 
-    /// Change the algorithm based on the selection and perform a reset.
+    /// Create the controls tuning the selected algorithm, if it has any.
+    /// Returns true if any of them has been changed.
+    fn handle_options(&mut self, ui: &mut Ui) -> bool {
+        let mut changed = false;
+        if self.custom.is_some() {
+            return changed;
+        }
+        if let Algorithms::LsdRadix | Algorithms::MsdRadix = self.selected {
+            ui.label("Base:");
+            changed |= ui
+                .add(DragValue::new(&mut self.options.base).clamp_range(MIN_BASE..=MAX_BASE))
+                .changed();
+        }
+        if self.selected == Algorithms::Shell {
+            ui.label("Gaps:");
+            ComboBox::from_id_source(2)
+                .selected_text(format!("{:?}", self.options.gaps))
+                .show_ui(ui, |ui| {
+                    for option in GapSequence::iter() {
+                        changed |= ui
+                            .selectable_value(&mut self.options.gaps, option, format!("{option:?}"))
+                            .changed();
+                    }
+                });
+        }
+        if self.selected == Algorithms::Quick {
+            ui.label("Partition:");
+            ComboBox::from_id_source(3)
+                .selected_text(format!("{:?}", self.options.partition))
+                .show_ui(ui, |ui| {
+                    for option in PartitionScheme::iter() {
+                        changed |= ui
+                            .selectable_value(
+                                &mut self.options.partition,
+                                option,
+                                format!("{option:?}"),
+                            )
+                            .changed();
+                    }
+                });
+            ui.label("Pivot:");
+            ComboBox::from_id_source(4)
+                .selected_text(format!("{:?}", self.options.pivot))
+                .show_ui(ui, |ui| {
+                    for option in PivotStrategy::iter() {
+                        changed |= ui
+                            .selectable_value(
+                                &mut self.options.pivot,
+                                option,
+                                format!("{option:?}"),
+                            )
+                            .changed();
+                    }
+                });
+        }
+        changed
+    }
+
+    /// Change the algorithm based on the selection, or to the custom network if there's one,
+    /// and perform a reset.
     fn switch_algorithm(&mut self) {
-        self.sorter = match self.selected {
-            Algorithms::Bubble => Box::new(BubbleSort::new()),
-            Algorithms::Selection => Box::new(SelectionSort::new()),
-            Algorithms::Insertion => Box::new(InsertionSort::new()),
-            Algorithms::Merge => Box::new(MergeSort::new()),
-            Algorithms::Bogo => Box::new(BogoSort::new()),
-            Algorithms::Heap => Box::new(HeapSort::new()),
-            Algorithms::Quick => Box::new(QuickSort::new()),
+        self.sorter = match &self.custom {
+            Some(network) => Box::new(NetworkSort::with_network(network.clone())),
+            None => self.selected.sorter_with(&self.options),
         };
         ButtonHandler::handle_reset(self);
     }
 
+    /// Run `network` on the numbers instead of the selected algorithm, leaving the race if racing.
+    fn run_network(&mut self, network: Network) {
+        self.custom = Some(network);
+        self.race = None;
+        self.switch_algorithm();
+    }
+
     /// Create buttons and handle their events.
     fn handle_buttons(&mut self, ui: &mut Ui) {
         if self.state == State::Running {
@@ -157,42 +511,277 @@ impl Visualizer<'_> {
             ui.add_enabled(false, Button::new("Step"));
         } else {
             if ui.add(Button::new("Start")).clicked() {
+                if self.state == State::Finished {
+                    self.reset();
+                }
                 self.state = State::Running;
             }
             if ui.add(Button::new("Step")).clicked() {
                 ButtonHandler::handle_step(self);
             }
         }
+        let can_step_back = self.state != State::Running && self.stats.steps > 0;
+        if ui
+            .add_enabled(can_step_back, Button::new("Step back"))
+            .clicked()
+        {
+            ButtonHandler::handle_step_back(self);
+        }
         if ui.add(Button::new("Reset")).clicked() {
             ButtonHandler::handle_reset(self);
         }
         if ui.add(Button::new("Shuffle")).clicked() {
             ButtonHandler::handle_shuffle(self);
         }
+        ui.toggle_value(&mut self.analysis.open, "Analysis");
+        ui.toggle_value(&mut self.designer.open, "Networks");
+        self.handle_race_toggle(ui);
     }
 
-    /// If running, take a step and sleep for WAIT_TIME.
-    fn handle_running(&mut self) {
-        if self.state == State::Running {
-            thread::sleep(WAIT_TIME);
+    /// Create the button that enters and leaves race mode.
+    /// The race starts with the selected algorithm against the one after it.
+    /// Custom networks don't race, as they only sort as many numbers as they have wires.
+    fn handle_race_toggle(&mut self, ui: &mut Ui) {
+        let mut racing = self.race.is_some();
+        let toggled = ui
+            .add_enabled_ui(self.custom.is_none(), |ui| {
+                ui.toggle_value(&mut racing, "Race").changed()
+            })
+            .inner;
+        if !toggled {
+            return;
+        }
+        self.race = racing.then(|| {
+            let rival = Algorithms::iter()
+                .cycle()
+                .skip_while(|&algorithm| algorithm != self.selected)
+                .nth(1)
+                .unwrap_or(self.selected);
+            Race::new(
+                &[self.selected, rival],
+                &self.options,
+                &self.original_numbers,
+                self.seed,
+            )
+        });
+        self.state = State::Start;
+    }
+
+    /// Create the timeline slider, spanning every step of the run computed so far,
+    /// and go to the selected step when it's dragged.
+    fn handle_timeline(&mut self, ui: &mut Ui) {
+        let deadline = Instant::now() + PRECOMPUTE_TIME;
+        while self.history.known() < PRECOMPUTE_LIMIT
+            && Instant::now() < deadline
+            && !self.history.extend(PRECOMPUTE_CHUNK)
+        {}
+        let text = match self.history.length() {
+            Some(_) => "Step".to_string(),
+            None if self.history.known() >= PRECOMPUTE_LIMIT => {
+                format!("Step (stopped computing at {})", self.history.known())
+            }
+            None => {
+                // Keep computing in the next frames, even if nothing else asks for them
+                ui.ctx().request_repaint();
+                "Step (computing...)".to_string()
+            }
+        };
+        let last = self.history.known().max(self.stats.steps);
+        let mut step = self.stats.steps;
+        ui.spacing_mut().slider_width = TIMELINE_WIDTH;
+        let slider = Slider::new(&mut step, 0..=last).text(text);
+        if ui
+            .add_enabled(self.state != State::Running, slider)
+            .changed()
+        {
+            ButtonHandler::handle_seek(self, step);
+        }
+    }
+
+    /// Create the controls for the size of the array, the range of its numbers, their shape
+    /// and the seed they are generated from.
+    /// Returns true if any of them has been changed.
+    fn handle_input_controls(&mut self, ui: &mut Ui) -> bool {
+        let mut changed = false;
+        ui.label("Size:");
+        changed |= ui
+            .add(DragValue::new(&mut self.size).clamp_range(0..=MAX_SIZE))
+            .changed();
+        // The ceiling is exclusive, but it's friendlier to show the highest number instead
+        let mut highest = self.ceil - 1;
+        ui.label("Numbers from");
+        changed |= ui
+            .add(DragValue::new(&mut self.floor).clamp_range(0..=highest))
+            .changed();
+        ui.label("to");
+        changed |= ui
+            .add(DragValue::new(&mut highest).clamp_range(self.floor..=MAX_VALUE))
+            .changed();
+        self.ceil = highest + 1;
+        ui.label("Input:");
+        ComboBox::from_id_source(1)
+            .selected_text(self.distribution.to_string())
+            .show_ui(ui, |ui| {
+                for option in Distribution::iter() {
+                    // Parameters of the distribution are kept until another one is picked
+                    let selected = discriminant(&self.distribution) == discriminant(&option);
+                    if ui.selectable_label(selected, option.to_string()).clicked() && !selected {
+                        self.distribution = option;
+                        changed = true;
+                    }
+                }
+            });
+        if let Distribution::NearlySorted(Swaps(swaps)) = &mut self.distribution {
+            ui.label("Swaps:");
+            changed |= ui
+                .add(DragValue::new(swaps).clamp_range(0..=MAX_SIZE))
+                .changed();
+        }
+        ui.label("Seed:");
+        changed |= ui.add(DragValue::new(&mut self.seed)).changed();
+        changed
+    }
+
+    /// Create the slider that controls how many steps are taken per second while running.
+    fn handle_speed(&mut self, ui: &mut Ui) {
+        let slider = Slider::new(&mut self.scheduler.speed, MIN_SPEED..=MAX_SPEED)
+            .logarithmic(true)
+            .text("Steps per second");
+        ui.add(slider);
+    }
+
+    /// If running, take the steps that are due by `now`.
+    /// Returns when the next step is due, so the UI can be repainted then.
+    fn handle_running(&mut self, now: Instant) -> Option<Duration> {
+        if self.state != State::Running {
+            self.scheduler.stop();
+            return None;
+        }
+        for _ in 0..self.scheduler.tick(now) {
             ButtonHandler::handle_step(self);
+            if self.state == State::Finished {
+                self.scheduler.stop();
+                return None;
+            }
+        }
+        Some(self.scheduler.until_next())
+    }
+
+    /// If racing, tick the race as many times as due by `now`.
+    /// Returns when the next tick is due, so the UI can be repainted then.
+    fn handle_race_running(&mut self, now: Instant) -> Option<Duration> {
+        let race = self.race.as_mut()?;
+        if !race.running {
+            self.scheduler.stop();
+            return None;
+        }
+        for _ in 0..self.scheduler.tick(now) {
+            race.tick();
+            if race.finished() {
+                race.running = false;
+                self.scheduler.stop();
+                return None;
+            }
         }
+        Some(self.scheduler.until_next())
     }
 
     /// Set all variables to their initial state.
+    /// The history starts over from the current numbers, and so does the race if racing.
     fn reset(&mut self) {
         self.state = State::Start;
         self.sorter.reset_state();
+        self.sorter.seed(self.seed);
+        self.stats = Stats::default();
+        self.history = History::new(&self.numbers, self.sorter.as_ref());
+        if let Some(race) = &mut self.race {
+            race.restart(&self.original_numbers, self.seed);
+        }
+    }
+}
+
+impl eframe::App for Visualizer<'_> {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        SidePanel::right(STATS_PANEL).show(ctx, |ui| {
+            ui.add_space(PADDING);
+            match &self.race {
+                Some(race) => race.draw_leaderboard(ui),
+                None => self.draw_stats(ui),
+            }
+        });
+        CentralPanel::default().show(ctx, |ui| {
+            // Horizontal is used to align the ComboBox with the buttons
+            ui.horizontal(|ui| {
+                ui.add_space(CENTRALIZE_PADDING);
+                match &mut self.race {
+                    Some(race) => {
+                        race.handle_controls(ui);
+                        self.handle_race_toggle(ui);
+                    }
+                    None => {
+                        let mut switched = self.handle_combo_box(ui);
+                        switched |= self.handle_options(ui);
+                        if switched {
+                            self.switch_algorithm();
+                        }
+                        self.handle_buttons(ui);
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.add_space(CENTRALIZE_PADDING);
+                if self.handle_input_controls(ui) {
+                    ButtonHandler::handle_generate(self);
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.add_space(CENTRALIZE_PADDING);
+                self.handle_speed(ui);
+            });
+
+            if self.race.is_none() {
+                ui.horizontal(|ui| {
+                    ui.add_space(CENTRALIZE_PADDING);
+                    self.handle_timeline(ui);
+                });
+            }
+
+            // Instead of blocking until the next step, ask egui to wake us up for it
+            let now = Instant::now();
+            let wait = match self.race {
+                Some(_) => self.handle_race_running(now),
+                None => self.handle_running(now),
+            };
+            if let Some(wait) = wait {
+                ctx.request_repaint_after(wait);
+            }
+
+            ui.add_space(PADDING);
+            let highest = self.original_numbers.iter().copied().max().unwrap_or(0);
+            match &mut self.race {
+                Some(race) => race.draw_lanes(ui, highest.max(self.ceil - 1)),
+                None => self.draw_numbers(ui),
+            }
+        });
+        let analysed = self.custom.is_none().then_some(self.selected);
+        self.analysis.show(ctx, analysed, &self.options, self.seed);
+        if let Some(network) = self.designer.show(ctx, self.numbers.len()) {
+            self.run_network(network);
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::time::Instant;
+    use std::time::{Duration, Instant};
 
     use crate::ui::State;
+    use eframe::epaint::Color32;
 
-    use super::{Visualizer, WAIT_TIME};
+    use super::{ButtonHandler, Options, Race, Visualizer};
+    use sorting_visualizer::algorithms::{
+        network_sort::Network, shell_sort::GapSequence, Algorithms,
+    };
 
     #[test]
     fn handle_running() {
@@ -200,9 +789,123 @@ mod tests {
             state: State::Running,
             ..Default::default()
         };
+        app.scheduler.speed = 10.;
         let now = Instant::now();
-        app.handle_running();
-        assert!(now.elapsed() >= WAIT_TIME);
+        // Nothing blocks, the first step is taken right away
+        assert_eq!(app.handle_running(now), Some(Duration::from_millis(100)));
+        assert_eq!(app.stats.steps, 1);
+        app.handle_running(now + Duration::from_millis(200));
+        assert_eq!(app.stats.steps, 3);
+
+        app.state = State::Start;
+        assert_eq!(app.handle_running(now + Duration::from_secs(1)), None);
+        assert_eq!(app.stats.steps, 3);
+    }
+
+    #[test]
+    fn handle_running_finished() {
+        let mut app = Visualizer {
+            numbers: vec![2, 1],
+            state: State::Running,
+            ..Default::default()
+        };
+        app.scheduler.speed = 1000.;
+        let now = Instant::now();
+        app.handle_running(now);
+        assert_eq!(app.handle_running(now + Duration::from_secs(1)), None);
+        assert_eq!(app.state, State::Finished);
+        assert_eq!(app.numbers, vec![1, 2]);
+    }
+
+    #[test]
+    fn handle_race_running() {
+        let options = Options::default();
+        let mut race = Race::new(
+            &[Algorithms::Bubble, Algorithms::Merge],
+            &options,
+            &[3, 1, 2],
+            0,
+        );
+        race.running = true;
+        let mut app = Visualizer {
+            race: Some(race),
+            ..Default::default()
+        };
+        app.scheduler.speed = 1000.;
+        let now = Instant::now();
+        assert!(app.handle_race_running(now).is_some());
+        assert_eq!(app.handle_race_running(now + Duration::from_secs(1)), None);
+        let race = app.race.as_ref().unwrap();
+        assert!(race.finished());
+        assert!(!race.running);
+        // The single algorithm is left alone while racing
+        assert_eq!(app.stats.steps, 0);
+    }
+
+    #[test]
+    fn colors() {
+        let mut app = Visualizer {
+            numbers: vec![5, 2, 6],
+            ..Default::default()
+        };
+        ButtonHandler::handle_step(&mut app);
+        assert_eq!(
+            app.colors(),
+            vec![Color32::LIGHT_YELLOW, Color32::LIGHT_YELLOW, Color32::GRAY]
+        );
+        ButtonHandler::handle_step(&mut app);
+        assert_eq!(
+            app.colors(),
+            vec![Color32::LIGHT_GREEN, Color32::LIGHT_GREEN, Color32::GRAY]
+        );
+    }
+
+    #[test]
+    fn switch_algorithm() {
+        let mut app = Visualizer {
+            numbers: vec![4, 3, 2, 1],
+            original_numbers: vec![4, 3, 2, 1],
+            selected: Algorithms::Shell,
+            ..Default::default()
+        };
+        app.options.gaps = GapSequence::Shell;
+        app.switch_algorithm();
+        ButtonHandler::handle_step(&mut app);
+        assert_eq!(app.sorter.status(), Some("Gap: 2".to_string()));
+
+        app.selected = Algorithms::LsdRadix;
+        app.options.base = 2;
+        app.switch_algorithm();
+        assert_eq!(app.sorter.buffers().len(), 2);
+    }
+
+    #[test]
+    fn run_network() {
+        let mut app = Visualizer {
+            numbers: vec![3, 2, 1],
+            original_numbers: vec![3, 2, 1],
+            selected: Algorithms::Shell,
+            ..Default::default()
+        };
+        // The network is only known to the sorter once it's started
+        let runs_network = |app: &mut Visualizer| {
+            ButtonHandler::handle_step(app);
+            app.sorter.network().is_some()
+        };
+        app.run_network(Network::odd_even_merge(3));
+        assert!(runs_network(&mut app));
+
+        // It's kept through resets and changes of the options
+        ButtonHandler::handle_reset(&mut app);
+        assert!(runs_network(&mut app));
+        app.options.gaps = GapSequence::Shell;
+        app.switch_algorithm();
+        assert!(runs_network(&mut app));
+
+        app.custom = None;
+        app.switch_algorithm();
+        assert!(!runs_network(&mut app));
+        assert_eq!(app.sorter.status(), Some("Gap: 1".to_string()));
     }
 
     #[test]
@@ -213,23 +916,3 @@ mod tests {
         assert_eq!(app.state, State::Start);
     }
 }
-
-impl eframe::App for Visualizer<'_> {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        CentralPanel::default().show(ctx, |ui| {
-            // Horizontal is used to align the ComboBox with the buttons
-            ui.horizontal(|ui| {
-                ui.add_space(CENTRALIZE_PADDING);
-                if self.handle_combo_box(ui) {
-                    self.switch_algorithm();
-                }
-                self.handle_buttons(ui);
-            });
-
-            self.handle_running();
-
-            ui.add_space(PADDING);
-            self.draw_numbers(ui);
-        });
-    }
-}
@@ -0,0 +1,120 @@
+use super::PADDING;
+use eframe::{
+    egui::{self, Ui},
+    epaint::{pos2, Color32, FontId, Rect, Stroke},
+};
+use sorting_visualizer::{algorithms::network_sort::Network, Operation};
+
+/// Layers are given at least this height, so the ones that don't fit are scrolled through.
+const MIN_LAYER_HEIGHT: f32 = 14.;
+const WIRE_COLOR: Color32 = Color32::from_gray(70);
+const WIRE_WIDTH: f32 = 1.;
+const PENDING_COLOR: Color32 = Color32::from_gray(200);
+const APPLIED_COLOR: Color32 = Color32::from_gray(90);
+const COMPARATOR_WIDTH: f32 = 2.;
+const DOT_RADIUS: f32 = 3.;
+
+/// Returns the slot of each comparator of `layer`, so comparators whose spans overlap
+/// are drawn one beneath the other rather than over each other.
+pub(super) fn slots(layer: &[(usize, usize)]) -> Vec<usize> {
+    // The last wire spanned by each slot so far
+    let mut ends: Vec<usize> = vec![];
+    layer
+        .iter()
+        .map(|&(a, b)| match ends.iter().position(|&end| end < a) {
+            Some(slot) => {
+                ends[slot] = b;
+                slot
+            }
+            None => {
+                ends.push(b);
+                ends.len() - 1
+            }
+        })
+        .collect()
+}
+
+/// Draws `network` into `rect` with its wires beneath the bars drawn by [`draw_bars`] into
+/// a rect as wide for an array of `len` numbers, one layer of comparators after the other
+/// going down. Wires past the end of the array, and the comparators touching them, are left out.
+/// The layers scroll along as they are `applied`, the last one applied colored by the
+/// `operations` it took.
+///
+/// [`draw_bars`]: super::draw_bars
+pub(super) fn draw_network(
+    ui: &Ui,
+    rect: Rect,
+    len: usize,
+    network: &Network,
+    applied: usize,
+    operations: &[Operation],
+) {
+    let rect = rect.shrink(PADDING);
+    let total = network.layers.len();
+    if len == 0 || total == 0 {
+        return;
+    }
+    let width = rect.width() / len as f32;
+    let x = |wire: usize| rect.left() + (wire as f32 + 0.5) * width;
+    let rows = ((rect.height() / MIN_LAYER_HEIGHT) as usize).clamp(1, total);
+    let height = rect.height() / rows as f32;
+    // The last layer applied is kept a third of the way down
+    let first = applied.saturating_sub(rows / 3).min(total - rows);
+
+    let painter = ui.painter();
+    for wire in 0..network.wires.min(len) {
+        painter.line_segment(
+            [pos2(x(wire), rect.top()), pos2(x(wire), rect.bottom())],
+            Stroke::new(WIRE_WIDTH, WIRE_COLOR),
+        );
+    }
+    for (row, index) in (first..first + rows).enumerate() {
+        let layer = &network.layers[index];
+        let slots = slots(layer);
+        let count = slots.iter().max().map_or(1, |&max| max + 1);
+        for (&(a, b), slot) in layer.iter().zip(slots) {
+            if b >= len {
+                continue;
+            }
+            let y = rect.top() + (row as f32 + (slot as f32 + 0.5) / count as f32) * height;
+            let color = if index + 1 == applied {
+                if operations.contains(&Operation::Swap(a, b)) {
+                    Color32::LIGHT_GREEN
+                } else {
+                    Color32::LIGHT_YELLOW
+                }
+            } else if index < applied {
+                APPLIED_COLOR
+            } else {
+                PENDING_COLOR
+            };
+            painter.line_segment(
+                [pos2(x(a), y), pos2(x(b), y)],
+                Stroke::new(COMPARATOR_WIDTH, color),
+            );
+            painter.circle_filled(pos2(x(a), y), DOT_RADIUS, color);
+            painter.circle_filled(pos2(x(b), y), DOT_RADIUS, color);
+        }
+    }
+    if rows < total {
+        painter.text(
+            rect.right_top(),
+            egui::Align2::RIGHT_TOP,
+            format!("Layers {}-{} of {total}", first + 1, first + rows),
+            FontId::default(),
+            ui.visuals().text_color(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::slots;
+
+    #[test]
+    fn overlapping() {
+        assert_eq!(slots(&[(0, 1), (2, 3)]), [0, 0]);
+        assert_eq!(slots(&[(0, 3), (1, 2), (4, 7), (5, 6)]), [0, 1, 0, 1]);
+        assert_eq!(slots(&[(0, 2), (1, 3), (4, 5)]), [0, 1, 0]);
+    }
+}
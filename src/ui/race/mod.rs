@@ -0,0 +1,426 @@
+use super::{draw_bars, operation_colors, PADDING};
+use eframe::egui::{self, Align, Button, ComboBox, Grid, Layout, Rect, Ui, Vec2};
+use sorting_visualizer::algorithms::{options::Options, stats::Stats, Algorithms, Sorter};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+/// Most steps a lane takes in a single tick, so a lane falling behind catches up gradually.
+const MAX_LANE_STEPS: usize = 1_000;
+/// Fewest lanes a race can have.
+const MIN_LANES: usize = 2;
+/// Most lanes a race can have.
+const MAX_LANES: usize = 8;
+const LEADERBOARD_GRID: &str = "leaderboard";
+
+/// How the lanes are kept even while racing.
+#[derive(PartialEq, Debug, EnumIter, Clone, Copy)]
+pub(crate) enum Pace {
+    /// Every lane takes a step per tick.
+    Steps,
+    /// Every lane takes steps until it has performed as many operations as the clock.
+    Operations,
+}
+
+/// How the lanes are arranged on the screen.
+#[derive(PartialEq, Debug, EnumIter, Clone, Copy)]
+pub(crate) enum Arrangement {
+    /// One lane above the other.
+    Stacked,
+    /// Lanes side by side in a grid.
+    Tiled,
+}
+
+/// An algorithm racing over its own copy of the numbers.
+pub(crate) struct Lane {
+    algorithm: Algorithms,
+    sorter: Box<dyn Sorter>,
+    numbers: Vec<usize>,
+    stats: Stats,
+    /// A paused lane is held back while the others keep going.
+    paused: bool,
+    /// The place the lane finished in, starting from 1.
+    place: Option<usize>,
+}
+
+impl Lane {
+    fn new(algorithm: Algorithms, options: &Options, numbers: &[usize], seed: u64) -> Self {
+        let mut sorter = algorithm.sorter_with(options);
+        sorter.seed(seed);
+        Lane {
+            algorithm,
+            sorter,
+            numbers: numbers.to_vec(),
+            stats: Stats::default(),
+            paused: false,
+            place: None,
+        }
+    }
+
+    /// Returns how far the lane has gone, measured in the units of `pace`.
+    fn cost(&self, pace: Pace) -> usize {
+        match pace {
+            Pace::Steps => self.stats.steps,
+            Pace::Operations => {
+                self.stats.comparisons
+                    + self.stats.swaps
+                    + self.stats.writes
+                    + self.stats.aux_writes
+            }
+        }
+    }
+}
+
+/// Many algorithms sorting the same numbers at the same pace.
+pub(crate) struct Race {
+    lanes: Vec<Lane>,
+    pub(crate) pace: Pace,
+    pub(crate) arrangement: Arrangement,
+    pub(crate) running: bool,
+    /// How far every lane is allowed to go, in the units of the pace. Moves by one every tick.
+    clock: usize,
+    /// The numbers every lane starts from.
+    numbers: Vec<usize>,
+    /// How the algorithms of every lane are tuned.
+    options: Options,
+    seed: u64,
+}
+
+impl Race {
+    /// Starts a race between the algorithms tuned by `options`, each sorting a copy of `numbers`.
+    pub(crate) fn new(
+        algorithms: &[Algorithms],
+        options: &Options,
+        numbers: &[usize],
+        seed: u64,
+    ) -> Self {
+        Race {
+            lanes: algorithms
+                .iter()
+                .map(|&algorithm| Lane::new(algorithm, options, numbers, seed))
+                .collect(),
+            pace: Pace::Steps,
+            arrangement: Arrangement::Stacked,
+            running: false,
+            clock: 0,
+            numbers: numbers.to_vec(),
+            options: *options,
+            seed,
+        }
+    }
+
+    /// Takes every lane back to the start, over new numbers.
+    pub(crate) fn restart(&mut self, numbers: &[usize], seed: u64) {
+        self.numbers = numbers.to_vec();
+        self.seed = seed;
+        self.reset();
+    }
+
+    /// Takes every lane back to the start.
+    pub(crate) fn reset(&mut self) {
+        let algorithms: Vec<Algorithms> = self.lanes.iter().map(|lane| lane.algorithm).collect();
+        self.lanes = algorithms
+            .into_iter()
+            .map(|algorithm| Lane::new(algorithm, &self.options, &self.numbers, self.seed))
+            .collect();
+        self.clock = 0;
+        self.running = false;
+    }
+
+    /// Returns true once every lane has finished.
+    pub(crate) fn finished(&self) -> bool {
+        self.lanes.iter().all(|lane| lane.place.is_some())
+    }
+
+    /// Moves the clock forward, and the lanes that aren't paused along with it.
+    pub(crate) fn tick(&mut self) {
+        if self.finished() {
+            return;
+        }
+        self.clock += 1;
+        let mut place = self.lanes.iter().filter_map(|lane| lane.place).count();
+        for lane in &mut self.lanes {
+            if lane.paused || lane.place.is_some() {
+                continue;
+            }
+            for _ in 0..MAX_LANE_STEPS {
+                if lane.cost(self.pace) >= self.clock {
+                    break;
+                }
+                let done = lane.sorter.step(&mut lane.numbers);
+                lane.stats.track(&*lane.sorter);
+                if done {
+                    place += 1;
+                    lane.place = Some(place);
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Returns the indexes of the lanes from first to last:
+    /// finished lanes by place, then the others by how far they've gone.
+    fn standings(&self) -> Vec<usize> {
+        let mut standings: Vec<usize> = (0..self.lanes.len()).collect();
+        standings.sort_by_key(|&i| {
+            let lane = &self.lanes[i];
+            (
+                lane.place.unwrap_or(usize::MAX),
+                std::cmp::Reverse(lane.stats.steps),
+            )
+        });
+        standings
+    }
+
+    /// Draws the standings of every lane.
+    pub(crate) fn draw_leaderboard(&self, ui: &mut Ui) {
+        ui.heading("Leaderboard");
+        ui.add_space(PADDING);
+        Grid::new(LEADERBOARD_GRID)
+            .num_columns(4)
+            .striped(true)
+            .show(ui, |ui| {
+                for header in ["Place", "Algorithm", "Steps", "Comparisons"] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+                for i in self.standings() {
+                    let lane = &self.lanes[i];
+                    ui.label(
+                        lane.place
+                            .map_or("-".to_string(), |place| place.to_string()),
+                    );
+                    ui.label(format!("{:?}Sort", lane.algorithm));
+                    ui.label(lane.stats.steps.to_string());
+                    ui.label(lane.stats.comparisons.to_string());
+                    ui.end_row();
+                }
+            });
+        if self.finished() {
+            ui.add_space(PADDING);
+            ui.label("Every lane has finished.");
+        }
+    }
+
+    /// Creates the controls of the whole race and handles their events.
+    pub(crate) fn handle_controls(&mut self, ui: &mut Ui) {
+        let text = if self.running { "Stop" } else { "Start" };
+        if ui.add(Button::new(text)).clicked() {
+            if self.finished() {
+                self.reset();
+            }
+            self.running = !self.running;
+        }
+        if ui.add_enabled(!self.running, Button::new("Step")).clicked() {
+            self.tick();
+        }
+        if ui.add(Button::new("Reset")).clicked() {
+            self.reset();
+        }
+        ui.label("Pace:");
+        let pace = self.pace;
+        ComboBox::from_id_source("race_pace")
+            .selected_text(format!("{:?}", self.pace))
+            .show_ui(ui, |ui| {
+                for option in Pace::iter() {
+                    ui.selectable_value(&mut self.pace, option, format!("{option:?}"));
+                }
+            });
+        // Lanes that went ahead in one pace would be unfair in the other
+        if pace != self.pace {
+            self.reset();
+        }
+        ComboBox::from_id_source("race_arrangement")
+            .selected_text(format!("{:?}", self.arrangement))
+            .show_ui(ui, |ui| {
+                for option in Arrangement::iter() {
+                    ui.selectable_value(&mut self.arrangement, option, format!("{option:?}"));
+                }
+            });
+        ui.add_enabled_ui(self.lanes.len() < MAX_LANES, |ui| {
+            ComboBox::from_id_source("race_add")
+                .selected_text("Add lane")
+                .show_ui(ui, |ui| {
+                    for option in Algorithms::iter() {
+                        if ui
+                            .selectable_label(false, format!("{option:?}Sort"))
+                            .clicked()
+                        {
+                            self.lanes.push(Lane::new(
+                                option,
+                                &self.options,
+                                &self.numbers,
+                                self.seed,
+                            ));
+                            self.reset();
+                        }
+                    }
+                });
+        });
+    }
+
+    /// Draws every lane in its own panel, with its own controls, filling the remaining space.
+    /// Bars are scaled so that `highest` reaches the top.
+    pub(crate) fn draw_lanes(&mut self, ui: &mut Ui, highest: usize) {
+        let (rect, _) = ui.allocate_exact_size(ui.available_size(), egui::Sense::hover());
+        let count = self.lanes.len();
+        let columns = match self.arrangement {
+            Arrangement::Stacked => 1,
+            Arrangement::Tiled => (count as f32).sqrt().ceil() as usize,
+        };
+        let rows = count.div_ceil(columns);
+        let size = Vec2::new(
+            rect.width() / columns as f32,
+            rect.height() / rows.max(1) as f32,
+        );
+
+        let mut changed = false;
+        let mut removed = None;
+        let removable = count > MIN_LANES;
+        for (i, lane) in self.lanes.iter_mut().enumerate() {
+            let min = rect.min + Vec2::new((i % columns) as f32, (i / columns) as f32) * size;
+            let mut ui = ui.child_ui(Rect::from_min_size(min, size), Layout::top_down(Align::Min));
+            ui.horizontal(|ui| {
+                let previous = lane.algorithm;
+                ComboBox::from_id_source(("race_lane", i))
+                    .selected_text(format!("{:?}Sort", lane.algorithm))
+                    .show_ui(ui, |ui| {
+                        for option in Algorithms::iter() {
+                            ui.selectable_value(
+                                &mut lane.algorithm,
+                                option,
+                                format!("{option:?}Sort"),
+                            );
+                        }
+                    });
+                changed |= previous != lane.algorithm;
+                let text = if lane.paused { "Resume" } else { "Pause" };
+                if ui
+                    .add_enabled(lane.place.is_none(), Button::new(text))
+                    .clicked()
+                {
+                    lane.paused = !lane.paused;
+                }
+                if ui.add_enabled(removable, Button::new("Remove")).clicked() {
+                    removed = Some(i);
+                }
+                ui.label(format!(
+                    "Steps: {}  Comparisons: {}",
+                    lane.stats.steps, lane.stats.comparisons
+                ));
+                if let Some(status) = lane.sorter.status() {
+                    ui.label(status);
+                }
+                if let Some(place) = lane.place {
+                    ui.strong(format!("Finished #{place}"));
+                }
+            });
+            let colors = operation_colors(
+                lane.sorter.operations(),
+                lane.numbers.len(),
+                lane.place.is_some(),
+            );
+            let (bars, _) = ui.allocate_exact_size(ui.available_size(), egui::Sense::hover());
+            draw_bars(&ui, bars, &lane.numbers, &colors, highest);
+        }
+
+        if let Some(i) = removed {
+            self.lanes.remove(i);
+            changed = true;
+        }
+        if changed {
+            self.reset();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Lane, Pace, Race};
+    use sorting_visualizer::algorithms::{options::Options, shell_sort::GapSequence, Algorithms};
+
+    #[test]
+    fn tick() {
+        let numbers = vec![5, 4, 3, 2, 1];
+        let options = Options::default();
+        let mut race = Race::new(
+            &[Algorithms::Bubble, Algorithms::Merge],
+            &options,
+            &numbers,
+            0,
+        );
+        race.tick();
+        assert!(race.lanes.iter().all(|lane| lane.stats.steps == 1));
+
+        // A paused lane stays behind
+        race.lanes[0].paused = true;
+        race.tick();
+        assert_eq!(race.lanes[0].stats.steps, 1);
+        assert_eq!(race.lanes[1].stats.steps, 2);
+        race.lanes[0].paused = false;
+
+        while !race.finished() {
+            race.tick();
+        }
+        let bubble = Algorithms::Bubble.sorter().run(&mut numbers.clone());
+        let merge = Algorithms::Merge.sorter().run(&mut numbers.clone());
+        assert_eq!(race.lanes[0].stats, bubble);
+        assert_eq!(race.lanes[1].stats, merge);
+        let winner = if bubble.steps <= merge.steps { 0 } else { 1 };
+        assert_eq!(race.lanes[winner].place, Some(1));
+        assert_eq!(race.standings()[0], winner);
+        assert!(race
+            .lanes
+            .iter()
+            .all(|lane| lane.numbers == [1, 2, 3, 4, 5]));
+
+        race.reset();
+        assert!(race.lanes.iter().all(|lane| lane.numbers == numbers));
+        assert!(!race.finished());
+    }
+
+    #[test]
+    fn options() {
+        let numbers = vec![6, 5, 4, 3, 2, 1];
+        let options = Options {
+            gaps: GapSequence::Shell,
+            ..Default::default()
+        };
+        let mut race = Race::new(
+            &[Algorithms::Shell, Algorithms::Shell],
+            &options,
+            &numbers,
+            0,
+        );
+        race.lanes
+            .push(Lane::new(Algorithms::Shell, &race.options, &numbers, 0));
+        while !race.finished() {
+            race.tick();
+        }
+        // Every lane, even one added later, is tuned by the options of the race
+        let stats = Algorithms::Shell
+            .sorter_with(&options)
+            .run(&mut numbers.clone());
+        assert!(race.lanes.iter().all(|lane| lane.stats == stats));
+        assert_ne!(stats, Algorithms::Shell.sorter().run(&mut numbers.clone()));
+    }
+
+    #[test]
+    fn tick_operations() {
+        let numbers = vec![4, 3, 2, 1];
+        let options = Options::default();
+        let mut race = Race::new(
+            &[Algorithms::Insertion, Algorithms::Heap],
+            &options,
+            &numbers,
+            0,
+        );
+        race.pace = Pace::Operations;
+        for _ in 0..3 {
+            race.tick();
+            for lane in &race.lanes {
+                // Lanes go up to the clock, overshooting by at most a step
+                assert!(lane.place.is_some() || lane.cost(Pace::Operations) >= race.clock);
+            }
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use std::time::{Duration, Instant};
+
+/// Slowest speed, in steps per second: a step every four seconds.
+pub const MIN_SPEED: f64 = 0.25;
+/// Fastest speed, in steps per second: thousands of steps per frame.
+pub const MAX_SPEED: f64 = 250_000.;
+/// Speed used when the app starts, in steps per second.
+pub const DEFAULT_SPEED: f64 = 8.;
+/// Most steps ever taken in a single frame, so a slow frame doesn't snowball into slower ones.
+const MAX_STEPS_PER_TICK: f64 = 10_000.;
+
+/// Decides how many steps should be taken while running,
+/// based on the time elapsed between frames and the chosen speed.
+pub struct Scheduler {
+    /// Steps per second.
+    pub speed: f64,
+    last_tick: Option<Instant>,
+    /// Steps that are due but haven't been taken yet, including fractions of a step.
+    pending: f64,
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Scheduler {
+            speed: DEFAULT_SPEED,
+            last_tick: None,
+            pending: 0.,
+        }
+    }
+}
+
+impl Scheduler {
+    /// Returns how many steps are due at `now`.
+    /// The first tick after a stop is always due a step, so starting feels immediate.
+    pub fn tick(&mut self, now: Instant) -> usize {
+        self.pending = match self.last_tick {
+            Some(last) => self.pending + now.duration_since(last).as_secs_f64() * self.speed,
+            None => 1.,
+        }
+        .min(MAX_STEPS_PER_TICK);
+        self.last_tick = Some(now);
+        let due = self.pending.floor();
+        self.pending -= due;
+        due as usize
+    }
+
+    /// Returns how long until the next step is due.
+    pub fn until_next(&self) -> Duration {
+        Duration::from_secs_f64((1. - self.pending).max(0.) / self.speed)
+    }
+
+    /// Forgets about elapsed time, to be called whenever running stops.
+    pub fn stop(&mut self) {
+        self.last_tick = None;
+        self.pending = 0.;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::Scheduler;
+
+    #[test]
+    fn tick() {
+        let mut scheduler = Scheduler {
+            speed: 10.,
+            ..Default::default()
+        };
+        let now = Instant::now();
+        assert_eq!(scheduler.tick(now), 1);
+        assert_eq!(scheduler.until_next(), Duration::from_millis(100));
+
+        assert_eq!(scheduler.tick(now + Duration::from_millis(50)), 0);
+        assert_eq!(scheduler.tick(now + Duration::from_millis(350)), 3);
+
+        scheduler.stop();
+        assert_eq!(scheduler.tick(now + Duration::from_secs(60)), 1);
+    }
+
+    #[test]
+    fn tick_fast() {
+        let mut scheduler = Scheduler {
+            speed: 100_000.,
+            ..Default::default()
+        };
+        let now = Instant::now();
+        scheduler.tick(now);
+        assert_eq!(scheduler.tick(now + Duration::from_millis(16)), 1600);
+        // A long frame doesn't make the next ones longer
+        assert_eq!(scheduler.tick(now + Duration::from_secs(16)), 10_000);
+    }
+}
@@ -0,0 +1,53 @@
+use super::ACTIVE_RANGE_COLOR;
+use eframe::{
+    egui::{self, Sense, Ui},
+    epaint::{pos2, vec2, Color32, FontId, Rect},
+};
+
+const ROW_HEIGHT: f32 = 16.;
+const ROW_SPACING: f32 = 2.;
+/// Most ranges listed, so deep stacks don't push everything else away.
+const MAX_ROWS: usize = 24;
+const TRACK_COLOR: Color32 = Color32::from_gray(60);
+const PENDING_COLOR: Color32 = Color32::from_rgb(50, 90, 130);
+
+/// Draws `ranges` of an array of `len` numbers as a stack, the last one on top,
+/// each as a span of a track that stands for the whole array, with `active` shaded.
+pub(super) fn draw_stack(
+    ui: &mut Ui,
+    len: usize,
+    ranges: &[(usize, usize)],
+    active: Option<(usize, usize)>,
+) {
+    if len == 0 {
+        return;
+    }
+    for &(lo, hi) in ranges.iter().rev().take(MAX_ROWS) {
+        let (row, _) =
+            ui.allocate_exact_size(vec2(ui.available_width(), ROW_HEIGHT), Sense::hover());
+        let unit = row.width() / len as f32;
+        let span = Rect::from_min_max(
+            pos2(row.left() + lo as f32 * unit, row.top()),
+            pos2(row.left() + (hi + 1) as f32 * unit, row.bottom()),
+        );
+        let color = if active == Some((lo, hi)) {
+            ACTIVE_RANGE_COLOR
+        } else {
+            PENDING_COLOR
+        };
+        let painter = ui.painter();
+        painter.rect_filled(row, 0., TRACK_COLOR);
+        painter.rect_filled(span, 0., color);
+        painter.text(
+            row.left_center() + vec2(2., 0.),
+            egui::Align2::LEFT_CENTER,
+            format!("{lo}-{hi}"),
+            FontId::monospace(ROW_HEIGHT - 4.),
+            Color32::WHITE,
+        );
+        ui.add_space(ROW_SPACING);
+    }
+    if ranges.len() > MAX_ROWS {
+        ui.label(format!("... and {} more", ranges.len() - MAX_ROWS));
+    }
+}
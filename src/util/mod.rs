@@ -1,6 +1,14 @@
 use rand::{distributions::Uniform, Rng};
 
-pub fn gen_random_vector(floor: usize, ceil: usize, n: usize) -> Vec<usize> {
+/// Returns `n` uniformly distributed numbers from `floor` (inclusive) to `ceil` (exclusive),
+/// drawn from `rng`, so a seeded generator always gives back the same numbers.
+pub fn gen_random_vector(floor: usize, ceil: usize, n: usize, rng: &mut impl Rng) -> Vec<usize> {
     let range = Uniform::new(floor, ceil);
-    rand::thread_rng().sample_iter(&range).take(n).collect()
+    rng.sample_iter(&range).take(n).collect()
+}
+
+/// Returns `value * numerator / denominator`, rounded down, which must fit in a `usize`.
+/// It's computed in `u128`, so the product doesn't overflow even for numbers near `usize::MAX`.
+pub(crate) fn scale(value: usize, numerator: usize, denominator: u128) -> usize {
+    (value as u128 * numerator as u128 / denominator) as usize
 }